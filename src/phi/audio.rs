@@ -0,0 +1,687 @@
+use phi::data::Rectangle;
+use std::collections::HashMap;
+use std::f64::consts::FRAC_PI_4;
+use sdl2::mixer::{self, Chunk, Music, Sdl2MixerContext};
+
+/// The amount of channels mixed at the same time. Requesting a sound while all
+/// of them are busy steals the oldest channel of lesser or equal priority.
+const CHANNELS: usize = 16;
+
+/// How far away from the listener, in pixels, a positional sound fades out
+/// completely.
+const FALLOFF: f64 = 400.0;
+
+/// How fast, in volume per second, music layers fade in and out.
+const LAYER_FADE_SPEED: f64 = 0.5;
+
+/// A handle to a sound effect loaded through `Audio::load_sound`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Sound(usize);
+
+/// How important a sound effect is when competing for a channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low = 0,
+    Normal = 1,
+    High = 2,
+}
+
+/// The volume groups a sound can belong to. The effective volume of a sound is
+/// the product of its own volume, the volume of its group and the master
+/// volume, all of them between 0 and 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Volumes {
+    pub master: f64,
+    pub music: f64,
+    pub sfx: f64,
+}
+
+impl Volumes {
+    pub fn effective_music(&self) -> f64 {
+        clamp_unit(self.master) * clamp_unit(self.music)
+    }
+
+    pub fn effective_sfx(&self) -> f64 {
+        clamp_unit(self.master) * clamp_unit(self.sfx)
+    }
+}
+
+impl Default for Volumes {
+    fn default() -> Volumes {
+        Volumes {
+            master: 1.0,
+            music: 0.6,
+            sfx: 0.8,
+        }
+    }
+}
+
+/// The operations `Audio` needs from the underlying mixer. Volumes are given
+/// between 0 and 1, panning between -1 (left) and 1 (right) and channels
+/// between 0 and `CHANNELS`.
+pub trait AudioBackend {
+    fn load_chunk(&mut self, path: &str) -> Result<usize, String>;
+    fn play_chunk(&mut self, chunk: usize, channel: usize, volume: f64, loops: i32) -> Result<(), String>;
+    fn set_channel_volume(&mut self, channel: usize, volume: f64);
+    fn set_channel_panning(&mut self, channel: usize, pan: f64);
+    fn halt_channel(&mut self, channel: usize);
+    fn is_channel_playing(&self, channel: usize) -> bool;
+
+    fn play_music(&mut self, path: &str, loops: i32, fade_ms: i32) -> Result<(), String>;
+    fn set_music_volume(&mut self, volume: f64);
+    fn halt_music(&mut self, fade_ms: i32);
+}
+
+/// Plays sounds through SDL2_mixer. Music is streamed from disk, whereas sound
+/// effects are decoded once and kept in memory.
+pub struct SdlBackend {
+    // Only needed for compressed formats, WAV files play without it.
+    _context: Option<Sdl2MixerContext>,
+    chunks: Vec<Chunk>,
+    music: Option<Music<'static>>,
+}
+
+impl SdlBackend {
+    pub fn new() -> Result<SdlBackend, String> {
+        let context = mixer::init(mixer::INIT_OGG).ok();
+        mixer::open_audio(44_100, mixer::DEFAULT_FORMAT, mixer::DEFAULT_CHANNELS, 1_024)?;
+        mixer::allocate_channels(CHANNELS as i32);
+
+        Ok(SdlBackend {
+            _context: context,
+            chunks: vec![],
+            music: None,
+        })
+    }
+}
+
+impl Drop for SdlBackend {
+    fn drop(&mut self) {
+        Music::halt();
+        self.music = None;
+        self.chunks.clear();
+        mixer::close_audio();
+    }
+}
+
+fn to_mixer_volume(volume: f64) -> i32 {
+    (clamp_unit(volume) * mixer::MAX_VOLUME as f64).round() as i32
+}
+
+impl AudioBackend for SdlBackend {
+    fn load_chunk(&mut self, path: &str) -> Result<usize, String> {
+        let chunk = Chunk::from_file(path)?;
+        self.chunks.push(chunk);
+        Ok(self.chunks.len() - 1)
+    }
+
+    fn play_chunk(&mut self, chunk: usize, channel: usize, volume: f64, loops: i32) -> Result<(), String> {
+        let channel = mixer::channel(channel as i32);
+        channel.set_volume(to_mixer_volume(volume));
+        channel.play(&self.chunks[chunk], loops).map(|_| ())
+    }
+
+    fn set_channel_volume(&mut self, channel: usize, volume: f64) {
+        mixer::channel(channel as i32).set_volume(to_mixer_volume(volume));
+    }
+
+    fn set_channel_panning(&mut self, channel: usize, pan: f64) {
+        // Constant power panning, so that sounds do not get quieter as they
+        // cross the center of the screen.
+        let angle = (pan.max(-1.0).min(1.0) + 1.0) * FRAC_PI_4;
+        let left = (angle.cos() * 255.0).round() as u8;
+        let right = (angle.sin() * 255.0).round() as u8;
+        let _ = mixer::channel(channel as i32).set_panning(left, right);
+    }
+
+    fn halt_channel(&mut self, channel: usize) {
+        mixer::channel(channel as i32).halt();
+    }
+
+    fn is_channel_playing(&self, channel: usize) -> bool {
+        mixer::channel(channel as i32).is_playing()
+    }
+
+    fn play_music(&mut self, path: &str, loops: i32, fade_ms: i32) -> Result<(), String> {
+        let music = Music::from_file(path)?;
+        music.fade_in(loops, fade_ms)?;
+        // The previous track is dropped only once the new one has started.
+        self.music = Some(music);
+        Ok(())
+    }
+
+    fn set_music_volume(&mut self, volume: f64) {
+        Music::set_volume(to_mixer_volume(volume));
+    }
+
+    fn halt_music(&mut self, fade_ms: i32) {
+        if fade_ms > 0 {
+            let _ = Music::fade_out(fade_ms);
+        } else {
+            Music::halt();
+        }
+    }
+}
+
+/// A backend which does not output anything, used when no audio device is
+/// available and for headless runs. One-shot sounds finish as soon as they
+/// start, while looping ones keep their channel busy until halted.
+pub struct NullBackend {
+    chunks: usize,
+    looping: [bool; CHANNELS],
+}
+
+impl NullBackend {
+    pub fn new() -> NullBackend {
+        NullBackend {
+            chunks: 0,
+            looping: [false; CHANNELS],
+        }
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn load_chunk(&mut self, _: &str) -> Result<usize, String> {
+        self.chunks += 1;
+        Ok(self.chunks - 1)
+    }
+
+    fn play_chunk(&mut self, _: usize, channel: usize, _: f64, loops: i32) -> Result<(), String> {
+        self.looping[channel] = loops != 0;
+        Ok(())
+    }
+
+    fn set_channel_volume(&mut self, _: usize, _: f64) {}
+
+    fn set_channel_panning(&mut self, _: usize, _: f64) {}
+
+    fn halt_channel(&mut self, channel: usize) {
+        self.looping[channel] = false;
+    }
+
+    fn is_channel_playing(&self, channel: usize) -> bool {
+        self.looping[channel]
+    }
+
+    fn play_music(&mut self, _: &str, _: i32, _: i32) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn set_music_volume(&mut self, _: f64) {}
+
+    fn halt_music(&mut self, _: i32) {}
+}
+
+/// One stem of an adaptive piece of music. All stems of a piece should have the
+/// same length, as they are started together and loop independently.
+#[derive(Clone, Copy, Debug)]
+pub struct Stem {
+    pub path: &'static str,
+    /// The intensity from which this stem can be heard.
+    pub threshold: f64,
+}
+
+struct Layer {
+    channel: usize,
+    threshold: f64,
+    volume: f64,
+}
+
+#[derive(Clone, Copy)]
+struct ChannelState {
+    sound: Option<Sound>,
+    priority: Priority,
+    volume: f64,
+    // The attenuation caused by the distance to the listener.
+    gain: f64,
+    // Whether the channel plays a music layer, in which case it belongs to
+    // the music volume group and may not be stolen.
+    music: bool,
+    // Incremented every time a sound starts, so that we can find the oldest
+    // channel when all of them are busy.
+    started: u64,
+}
+
+/// Front-end to the audio subsystem: caches sounds by path, hands channels to
+/// sound effects and applies the volume groups.
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    cached_sounds: HashMap<&'static str, Sound>,
+    channels: [ChannelState; CHANNELS],
+    plays: u64,
+    volumes: Volumes,
+    listener: Option<Rectangle>,
+    layers: Vec<Layer>,
+    intensity: f64,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Audio {
+        let mut audio = Audio {
+            backend: backend,
+            cached_sounds: HashMap::new(),
+            channels: [ChannelState {
+                sound: None,
+                priority: Priority::Low,
+                volume: 0.0,
+                gain: 1.0,
+                music: false,
+                started: 0,
+            }; CHANNELS],
+            plays: 0,
+            volumes: Volumes::default(),
+            listener: None,
+            layers: vec![],
+            intensity: 0.0,
+        };
+
+        let volumes = audio.volumes;
+        audio.set_volumes(volumes);
+        audio
+    }
+
+    /// Opens the audio device, falling back to the null backend if that is not
+    /// possible so that the game remains playable without sound.
+    pub fn open() -> Audio {
+        match SdlBackend::new() {
+            Ok(backend) => Audio::new(Box::new(backend)),
+            Err(e) => {
+                eprintln!("Could not open the audio device, sound is disabled: {}", e);
+                Audio::headless()
+            }
+        }
+    }
+
+    pub fn headless() -> Audio {
+        Audio::new(Box::new(NullBackend::new()))
+    }
+
+    pub fn load_sound(&mut self, path: &'static str) -> Option<Sound> {
+        if let Some(sound) = self.cached_sounds.get(path) {
+            return Some(*sound);
+        }
+
+        match self.backend.load_chunk(path) {
+            Ok(id) => {
+                let sound = Sound(id);
+                self.cached_sounds.insert(path, sound);
+                Some(sound)
+            }
+            Err(e) => {
+                eprintln!("Could not load sound {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Plays a sound once at full volume, wherever the listener is, as
+    /// befits menus and other interface sounds.
+    pub fn play(&mut self, sound: Sound) -> Option<usize> {
+        self.play_with(sound, 1.0, Priority::Normal)
+    }
+
+    /// Plays a sound once and returns the channel it was assigned to, if any.
+    pub fn play_with(&mut self, sound: Sound, volume: f64, priority: Priority) -> Option<usize> {
+        self.start(sound, volume, (0.0, 1.0), priority, 0)
+    }
+
+    /// Sets the area of the world sounds are heard from, usually the camera or
+    /// the player. Positional sounds are played centered until it is set.
+    pub fn set_listener(&mut self, listener: Rectangle) {
+        self.listener = Some(listener);
+    }
+
+    /// Plays a sound once as if emitted from the point `(x, y)` of the world.
+    pub fn play_at(&mut self, sound: Sound, x: f64, y: f64, priority: Priority) -> Option<usize> {
+        let placement = self.placement(x, y);
+        if placement.1 <= 0.0 {
+            return None;
+        }
+
+        self.start(sound, 1.0, placement, priority, 0)
+    }
+
+    pub fn stop(&mut self, channel: usize) {
+        self.backend.halt_channel(channel);
+        self.channels[channel].sound = None;
+    }
+
+    /// Streams a music track from disk, looping forever and replacing
+    /// whichever track or layers were playing before.
+    pub fn play_music(&mut self, path: &str, fade_in: f64) {
+        self.stop_layers();
+
+        if let Err(e) = self.backend.play_music(path, -1, (fade_in * 1_000.0) as i32) {
+            eprintln!("Could not play music {}: {}", path, e);
+        }
+    }
+
+    /// Starts a piece of music made of stems which fade in and out as the
+    /// intensity set through `set_intensity` crosses their thresholds.
+    pub fn play_layers(&mut self, stems: &[Stem]) {
+        self.stop_layers();
+        self.backend.halt_music(0);
+
+        for stem in stems {
+            let sound = match self.load_sound(stem.path) {
+                Some(sound) => sound,
+                None => continue,
+            };
+
+            let volume = if self.intensity >= stem.threshold { 1.0 } else { 0.0 };
+
+            if let Some(channel) = self.start(sound, volume, (0.0, 1.0), Priority::High, -1) {
+                self.channels[channel].music = true;
+                self.update_channel_volume(channel);
+
+                self.layers.push(Layer {
+                    channel: channel,
+                    threshold: stem.threshold,
+                    volume: volume,
+                });
+            }
+        }
+    }
+
+    pub fn stop_layers(&mut self) {
+        let channels: Vec<usize> = self.layers.drain(..).map(|layer| layer.channel).collect();
+        for channel in channels {
+            self.channels[channel].music = false;
+            self.stop(channel);
+        }
+    }
+
+    /// Sets how intense the game currently is, usually between 0 and 1.
+    pub fn set_intensity(&mut self, intensity: f64) {
+        self.intensity = intensity;
+    }
+
+    /// Advances the fades of the music layers, should be called once per frame.
+    pub fn update(&mut self, elapsed: f64) {
+        let step = LAYER_FADE_SPEED * elapsed;
+        let mut changed = vec![];
+
+        for layer in &mut self.layers {
+            let target = if self.intensity >= layer.threshold { 1.0 } else { 0.0 };
+
+            if layer.volume < target {
+                layer.volume = (layer.volume + step).min(target);
+            } else if layer.volume > target {
+                layer.volume = (layer.volume - step).max(target);
+            } else {
+                continue;
+            }
+
+            changed.push((layer.channel, layer.volume));
+        }
+
+        for (channel, volume) in changed {
+            self.channels[channel].volume = volume;
+            self.update_channel_volume(channel);
+        }
+    }
+
+    pub fn volumes(&self) -> Volumes {
+        self.volumes
+    }
+
+    pub fn set_volumes(&mut self, volumes: Volumes) {
+        self.volumes = volumes;
+        self.backend.set_music_volume(volumes.effective_music());
+
+        for channel in 0..CHANNELS {
+            if self.channels[channel].sound.is_some() {
+                self.update_channel_volume(channel);
+            }
+        }
+    }
+
+    fn update_channel_volume(&mut self, channel: usize) {
+        let volume = self.channel_volume(channel);
+        self.backend.set_channel_volume(channel, volume);
+    }
+
+    /// The volume a channel is mixed at: that of its sound, attenuated by the
+    /// distance to the listener and multiplied by its volume group.
+    fn channel_volume(&self, channel: usize) -> f64 {
+        let state = self.channels[channel];
+        let group = if state.music {
+            self.volumes.effective_music()
+        } else {
+            self.volumes.effective_sfx()
+        };
+
+        state.volume * state.gain * group
+    }
+
+    /// Returns the panning and the attenuation of a sound emitted from
+    /// `(x, y)` as heard by the listener.
+    fn placement(&self, x: f64, y: f64) -> (f64, f64) {
+        let listener = match self.listener {
+            Some(listener) => listener,
+            None => return (0.0, 1.0),
+        };
+
+        let half_w = listener.w / 2.0;
+        let pan = if half_w > 0.0 {
+            ((x - listener.x - half_w) / half_w).max(-1.0).min(1.0)
+        } else {
+            0.0
+        };
+
+        // Sounds are heard at full volume anywhere inside of the listener and
+        // fade out linearly over `FALLOFF` pixels outside of it.
+        let dx = (listener.x - x).max(x - listener.x - listener.w).max(0.0);
+        let dy = (listener.y - y).max(y - listener.y - listener.h).max(0.0);
+        let gain = 1.0 - ((dx * dx + dy * dy).sqrt() / FALLOFF).min(1.0);
+
+        (pan, gain)
+    }
+
+    fn start(&mut self, sound: Sound, volume: f64, placement: (f64, f64), priority: Priority, loops: i32) -> Option<usize> {
+        let channel = match self.find_channel(priority) {
+            Some(channel) => channel,
+            None => return None,
+        };
+
+        let Sound(chunk) = sound;
+        let (pan, gain) = placement;
+
+        self.plays += 1;
+        self.channels[channel] = ChannelState {
+            sound: Some(sound),
+            priority: priority,
+            volume: clamp_unit(volume),
+            gain: gain,
+            music: false,
+            started: self.plays,
+        };
+
+        let effective = self.channel_volume(channel);
+        self.backend.set_channel_panning(channel, pan);
+
+        if let Err(e) = self.backend.play_chunk(chunk, channel, effective, loops) {
+            eprintln!("Could not play sound: {}", e);
+            self.channels[channel].sound = None;
+            return None;
+        }
+
+        Some(channel)
+    }
+
+    fn find_channel(&mut self, priority: Priority) -> Option<usize> {
+        for channel in 0..CHANNELS {
+            if self.channels[channel].sound.is_some() && !self.backend.is_channel_playing(channel) {
+                self.channels[channel].sound = None;
+            }
+        }
+
+        if let Some(free) = (0..CHANNELS).find(|&c| self.channels[c].sound.is_none()) {
+            return Some(free);
+        }
+
+        let stolen = (0..CHANNELS)
+            .filter(|&c| !self.channels[c].music && self.channels[c].priority <= priority)
+            .min_by_key(|&c| (self.channels[c].priority, self.channels[c].started));
+
+        if let Some(channel) = stolen {
+            self.stop(channel);
+        }
+
+        stolen
+    }
+}
+
+fn clamp_unit(value: f64) -> f64 {
+    value.max(0.0).min(1.0)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    /// Keeps every sound playing until it is halted, so that channels fill
+    /// up.
+    struct HeldBackend {
+        playing: [bool; CHANNELS],
+    }
+
+    impl AudioBackend for HeldBackend {
+        fn load_chunk(&mut self, _: &str) -> Result<usize, String> {
+            Ok(0)
+        }
+
+        fn play_chunk(&mut self, _: usize, channel: usize, _: f64, _: i32) -> Result<(), String> {
+            self.playing[channel] = true;
+            Ok(())
+        }
+
+        fn set_channel_volume(&mut self, _: usize, _: f64) {}
+
+        fn set_channel_panning(&mut self, _: usize, _: f64) {}
+
+        fn halt_channel(&mut self, channel: usize) {
+            self.playing[channel] = false;
+        }
+
+        fn is_channel_playing(&self, channel: usize) -> bool {
+            self.playing[channel]
+        }
+
+        fn play_music(&mut self, _: &str, _: i32, _: i32) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn set_music_volume(&mut self, _: f64) {}
+
+        fn halt_music(&mut self, _: i32) {}
+    }
+
+    fn held() -> Audio {
+        Audio::new(Box::new(HeldBackend { playing: [false; CHANNELS] }))
+    }
+
+    #[test]
+    fn finished_sounds_free_their_channel() {
+        let mut audio = Audio::headless();
+        let sound = audio.load_sound("shot.wav").unwrap();
+
+        // The null backend finishes one-shot sounds right away.
+        for _ in 0..CHANNELS * 2 {
+            assert_eq!(audio.play(sound), Some(0));
+        }
+    }
+
+    #[test]
+    fn steals_the_oldest_channel_of_lesser_priority() {
+        let mut audio = held();
+        let sound = audio.load_sound("engine.wav").unwrap();
+
+        for channel in 0..CHANNELS {
+            assert_eq!(audio.play(sound), Some(channel));
+        }
+
+        // Equal priority steals the oldest sound, then the next oldest.
+        assert_eq!(audio.play_with(sound, 1.0, Priority::Normal), Some(0));
+        assert_eq!(audio.play_with(sound, 1.0, Priority::High), Some(1));
+
+        // Low priority sounds cannot steal anything.
+        assert_eq!(audio.play_with(sound, 1.0, Priority::Low), None);
+
+        // Less important sounds are stolen first, whatever their age.
+        assert_eq!(audio.play_with(sound, 1.0, Priority::High), Some(2));
+        assert_eq!(audio.channels[1].priority, Priority::High);
+    }
+
+    #[test]
+    fn music_layers_are_never_stolen() {
+        let mut audio = held();
+        audio.play_layers(&[Stem { path: "drums.wav", threshold: 0.0 }]);
+        let sound = audio.load_sound("engine.wav").unwrap();
+
+        for _ in 1..CHANNELS {
+            assert!(audio.play_with(sound, 1.0, Priority::High).is_some());
+        }
+
+        for _ in 0..CHANNELS {
+            assert_ne!(audio.play_with(sound, 1.0, Priority::High), Some(0));
+        }
+
+        assert!(audio.channels[0].music);
+    }
+
+    #[test]
+    fn volume_groups_multiply() {
+        let mut audio = held();
+        audio.set_volumes(Volumes { master: 0.5, music: 0.8, sfx: 0.4 });
+        audio.play_layers(&[Stem { path: "drums.wav", threshold: 0.0 }]);
+
+        let sound = audio.load_sound("shot.wav").unwrap();
+        let effect = audio.play(sound).unwrap();
+        let quiet = audio.play_with(sound, 0.5, Priority::Normal).unwrap();
+
+        // Half the falloff away from the listener halves the volume again.
+        audio.set_listener(Rectangle { x: 0.0, y: 0.0, w: 800.0, h: 600.0 });
+        let far = audio.play_at(sound, 400.0, 600.0 + FALLOFF / 2.0, Priority::Normal).unwrap();
+
+        assert_close(audio.channel_volume(0), 0.5 * 0.8);
+        assert_close(audio.channel_volume(effect), 0.5 * 0.4);
+        assert_close(audio.channel_volume(quiet), 0.5 * 0.5 * 0.4);
+        assert_close(audio.channel_volume(far), 0.5 * 0.5 * 0.4);
+
+        // Groups are clamped, and changing them applies to what is playing.
+        audio.set_volumes(Volumes { master: 2.0, music: 1.0, sfx: -1.0 });
+        assert_close(audio.channel_volume(0), 1.0);
+        assert_close(audio.channel_volume(effect), 0.0);
+    }
+    #[test]
+    fn pans_and_attenuates_around_the_listener() {
+        let mut audio = Audio::headless();
+        assert_eq!(audio.placement(-500.0, 0.0), (0.0, 1.0));
+
+        audio.set_listener(Rectangle { x: 0.0, y: 0.0, w: 800.0, h: 600.0 });
+
+        assert_eq!(audio.placement(0.0, 300.0), (-1.0, 1.0));
+        assert_eq!(audio.placement(400.0, 300.0), (0.0, 1.0));
+        assert_eq!(audio.placement(600.0, 0.0), (0.5, 1.0));
+        assert_eq!(audio.placement(800.0, 600.0), (1.0, 1.0));
+
+        // Outside of the listener, sounds fade out and stay on their side.
+        let (pan, gain) = audio.placement(800.0 + FALLOFF / 2.0, 300.0);
+        assert_eq!(pan, 1.0);
+        assert_close(gain, 0.5);
+
+        let (pan, gain) = audio.placement(-FALLOFF / 4.0, 600.0 + FALLOFF / 4.0);
+        assert_eq!(pan, -1.0);
+        assert_close(gain, 1.0 - 0.5f64.sqrt() / 2.0);
+
+        let sound = audio.load_sound("explosion.wav").unwrap();
+        assert_eq!(audio.play_at(sound, 400.0, -FALLOFF, Priority::High), None);
+        assert_eq!(audio.play_at(sound, 400.0, 300.0, Priority::High), Some(0));
+    }
+}
@@ -0,0 +1,107 @@
+/// Keeps the time of the game, which can be paused, slowed down, sped up or
+/// briefly frozen independently of the real time. Menus and other UI
+/// animations should keep going in real time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Clock {
+    // The real time elapsed during the last tick.
+    real_elapsed: f64,
+    scale: f64,
+    paused: bool,
+    // The real time left to a freeze frame.
+    frozen_for: f64,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        Clock {
+            real_elapsed: 0.0,
+            scale: 1.0,
+            paused: false,
+            frozen_for: 0.0,
+        }
+    }
+
+    /// Advances the clock by `real` seconds of real time, and returns the
+    /// time which elapsed in the game.
+    pub fn tick(&mut self, real: f64) -> f64 {
+        self.real_elapsed = real;
+
+        if self.paused {
+            0.0
+        } else if self.frozen_for > 0.0 {
+            self.frozen_for = (self.frozen_for - real).max(0.0);
+            0.0
+        } else {
+            real * self.scale
+        }
+    }
+
+    /// The time which really elapsed during the last tick, whatever happened
+    /// to the clock.
+    pub fn real_elapsed(&self) -> f64 {
+        self.real_elapsed
+    }
+
+    /// How fast time goes by in the game, pauses and freezes aside.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Sets how fast time goes by in the game: below 1 for slow motion,
+    /// above 1 to speed it up.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale.max(0.0);
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Stops the game for `duration` seconds of real time, to give weight to
+    /// an impact. Overlapping freezes do not add up.
+    pub fn freeze(&mut self, duration: f64) {
+        self.frozen_for = self.frozen_for.max(duration);
+    }
+
+    /// Goes back to normal time, as when switching to another view.
+    pub fn reset(&mut self) {
+        *self = Clock::new();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_pauses_and_freezes() {
+        let mut clock = Clock::new();
+        assert_eq!(clock.tick(0.5), 0.5);
+
+        clock.set_scale(0.25);
+        assert_eq!(clock.tick(0.5), 0.125);
+        assert_eq!(clock.real_elapsed(), 0.5);
+
+        clock.pause();
+        assert_eq!(clock.tick(0.5), 0.0);
+        clock.resume();
+
+        // Freezes last in real time, whatever the scale.
+        clock.freeze(0.75);
+        clock.freeze(0.25);
+        assert_eq!(clock.tick(0.5), 0.0);
+        assert_eq!(clock.tick(0.5), 0.0);
+        assert_eq!(clock.tick(0.5), 0.125);
+
+        clock.set_scale(-1.0);
+        assert_eq!(clock.scale(), 0.0);
+
+        clock.reset();
+        assert_eq!(clock.tick(0.5), 0.5);
+    }
+}
@@ -1,5 +1,98 @@
+use sdl2::image::LoadSurface;
+use sdl2::pixels::Color;
 use sdl2::rect::Rect as SdlRect;
+use sdl2::surface::Surface;
+use std::collections::HashMap;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 
+/// A point or a displacement in 2D.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vec2 {
+    pub fn new(x: f64, y: f64) -> Vec2 {
+        Vec2 { x: x, y: y }
+    }
+
+    pub fn dot(self, other: Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// The vector with the same direction and a length of 1, or zero if it
+    /// has no direction.
+    pub fn normalized(self) -> Vec2 {
+        let length = self.length();
+
+        if length == 0.0 {
+            self
+        } else {
+            self / length
+        }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, factor: f64) -> Vec2 {
+        Vec2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Div<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, divisor: f64) -> Vec2 {
+        Vec2::new(self.x / divisor, self.y / divisor)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+/// An axis-aligned rectangle. Its area includes its left and top edges, but
+/// not its right and bottom ones, so that rectangles laid side by side do not
+/// overlap.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Rectangle {
     pub x: f64,
@@ -9,50 +102,771 @@ pub struct Rectangle {
 }
 
 impl Rectangle {
+    pub fn from_center(center: Vec2, w: f64, h: f64) -> Rectangle {
+        Rectangle {
+            x: center.x - w / 2.0,
+            y: center.y - h / 2.0,
+            w: w,
+            h: h,
+        }
+    }
+
+    /// Converts the rectangle to SDL's integer coordinates. Negative sizes
+    /// are flipped around the origin of the rectangle.
     pub fn to_sdl(self) -> SdlRect {
-        assert!(self.w >= 0.0 && self.h >= 0.0);
+        let rect = self.normalized();
+        SdlRect::new(rect.x as i32, rect.y as i32, rect.w as u32, rect.h as u32)
+    }
 
-        SdlRect::new(self.x as i32, self.y as i32, self.w as u32, self.h as u32)
+    /// The same area, with a positive width and height.
+    pub fn normalized(self) -> Rectangle {
+        Rectangle {
+            x: self.x.min(self.x + self.w),
+            y: self.y.min(self.y + self.h),
+            w: self.w.abs(),
+            h: self.h.abs(),
+        }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(self.x + self.w / 2.0, self.y + self.h / 2.0)
     }
 
+    pub fn translate(self, by: Vec2) -> Rectangle {
+        Rectangle {
+            x: self.x + by.x,
+            y: self.y + by.y,
+            ..self
+        }
+    }
+
+    /// Grows or shrinks the rectangle by `factor`, keeping its center in
+    /// place.
+    pub fn scale(self, factor: f64) -> Rectangle {
+        Rectangle::from_center(self.center(), self.w * factor, self.h * factor)
+    }
+
+    /// Moves the rectangle inside of `parent`, or returns `None` if it is too
+    /// large to fit.
     pub fn move_inside(self, parent: Rectangle) -> Option<Rectangle> {
         if self.w > parent.w || self.h > parent.h {
-            return None;
+            None
+        } else {
+            Some(self.clamp(parent))
         }
+    }
 
-        Some(Rectangle {
-                 w: self.w,
-                 h: self.h,
-                 x: if self.x < parent.x {
-                     parent.x
-                 } else if self.x + self.w >= parent.x + parent.w {
-            parent.x + parent.w - self.w
-        } else {
-            self.x
-        },
-                 y: if self.y < parent.y {
-                     parent.y
-                 } else if self.y + self.h >= parent.y + parent.h {
-            parent.y + parent.h - self.h
-        } else {
-            self.y
-        },
-             })
+    /// Moves the rectangle inside of `bounds`, shrinking it first if it is
+    /// too large to fit.
+    pub fn clamp(self, bounds: Rectangle) -> Rectangle {
+        let w = self.w.min(bounds.w);
+        let h = self.h.min(bounds.h);
+
+        Rectangle {
+            x: self.x.max(bounds.x).min(bounds.x + bounds.w - w),
+            y: self.y.max(bounds.y).min(bounds.y + bounds.h - h),
+            w: w,
+            h: h,
+        }
     }
 
     pub fn contains(&self, rect: Rectangle) -> bool {
-        let xmin = rect.x;
-        let xmax = xmin + rect.w;
-        let ymin = rect.y;
-        let ymax = ymin + rect.h;
+        rect.x >= self.x && rect.x + rect.w <= self.x + self.w &&
+        rect.y >= self.y && rect.y + rect.h <= self.y + self.h
+    }
 
-        xmin >= self.x && xmin <= self.x + self.w && xmax >= self.x &&
-        xmax <= self.x + self.w && ymin >= self.y && ymin <= self.y + self.h &&
-        ymax >= self.y && ymax <= self.y + self.h
+    /// Whether `point` lies inside of the rectangle. Like pixels, rectangles
+    /// include their top and left edges but not their bottom and right ones.
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        point.x >= self.x && point.x < self.x + self.w &&
+        point.y >= self.y && point.y < self.y + self.h
     }
 
     pub fn overlaps(&self, other: Rectangle) -> bool {
         self.x < other.x + other.w && self.x + self.w > other.x && self.y < other.y + other.h &&
         self.y + self.h > other.y
     }
+
+    /// The area covered by both rectangles, if any.
+    pub fn intersection(&self, other: Rectangle) -> Option<Rectangle> {
+        if !self.overlaps(other) {
+            return None;
+        }
+
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+
+        Some(Rectangle {
+            x: x,
+            y: y,
+            w: (self.x + self.w).min(other.x + other.w) - x,
+            h: (self.y + self.h).min(other.y + other.h) - y,
+        })
+    }
+
+    /// The smallest rectangle covering both rectangles.
+    pub fn union(&self, other: Rectangle) -> Rectangle {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+
+        Rectangle {
+            x: x,
+            y: y,
+            w: (self.x + self.w).max(other.x + other.w) - x,
+            h: (self.y + self.h).max(other.y + other.h) - y,
+        }
+    }
+}
+
+/// A broad phase for collisions: a uniform grid which buckets rectangles by
+/// the cells they cover, so that only rectangles sharing a cell are compared.
+/// Rectangles are identified by the order in which they were inserted.
+pub struct SpatialHash {
+    cell_size: f64,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    rects: Vec<Rectangle>,
+}
+
+impl SpatialHash {
+    /// Cells should be about the size of the larger objects being hashed.
+    pub fn new(cell_size: f64) -> SpatialHash {
+        assert!(cell_size > 0.0);
+
+        SpatialHash {
+            cell_size: cell_size,
+            cells: HashMap::new(),
+            rects: vec![],
+        }
+    }
+
+    /// Removes every rectangle, keeping the allocated buckets around for the
+    /// next frame.
+    pub fn clear(&mut self) {
+        for bucket in self.cells.values_mut() {
+            bucket.clear();
+        }
+
+        self.rects.clear();
+    }
+
+    pub fn insert(&mut self, rect: Rectangle) -> usize {
+        let id = self.rects.len();
+        self.rects.push(rect);
+
+        let (min, max) = self.cell_range(rect);
+        for cx in min.0..max.0 + 1 {
+            for cy in min.1..max.1 + 1 {
+                self.cells.entry((cx, cy)).or_insert_with(Vec::new).push(id);
+            }
+        }
+
+        id
+    }
+
+    /// The rectangles overlapping `rect`, in the order in which they were
+    /// inserted.
+    pub fn query(&self, rect: Rectangle) -> Vec<usize> {
+        let (min, max) = self.cell_range(rect);
+        let mut found = vec![];
+
+        for cx in min.0..max.0 + 1 {
+            for cy in min.1..max.1 + 1 {
+                if let Some(bucket) = self.cells.get(&(cx, cy)) {
+                    found.extend(bucket.iter().cloned().filter(|&id| self.rects[id].overlaps(rect)));
+                }
+            }
+        }
+
+        // Rectangles covering several cells are found once per cell.
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    /// Every pair of inserted rectangles which may overlap, as `(a, b)` with
+    /// `a < b`. Pairs only ever come from a shared cell, so the exact test is
+    /// left to the caller.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+
+        for bucket in self.cells.values() {
+            for (i, &a) in bucket.iter().enumerate() {
+                for &b in &bucket[i + 1..] {
+                    pairs.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    fn cell_range(&self, rect: Rectangle) -> ((i32, i32), (i32, i32)) {
+        let cell = |v: f64| (v / self.cell_size).floor() as i32;
+
+        ((cell(rect.x), cell(rect.y)),
+         (cell(rect.x + rect.w), cell(rect.y + rect.h)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Circle {
+    pub x: f64,
+    pub y: f64,
+    pub r: f64,
+}
+
+impl Circle {
+    /// The largest circle fitting inside `rect`.
+    pub fn inside(rect: Rectangle) -> Circle {
+        let center = rect.center();
+
+        Circle {
+            x: center.x,
+            y: center.y,
+            r: rect.w.min(rect.h) / 2.0,
+        }
+    }
+
+    pub fn overlaps_rect(&self, rect: Rectangle) -> bool {
+        // The point of the rectangle closest to the center.
+        let x = self.x.max(rect.x).min(rect.x + rect.w);
+        let y = self.y.max(rect.y).min(rect.y + rect.h);
+
+        (self.x - x).powi(2) + (self.y - y).powi(2) < self.r * self.r
+    }
+}
+
+/// A convex polygon, whose points are listed in order around it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polygon {
+    points: Vec<(f64, f64)>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<(f64, f64)>) -> Polygon {
+        assert!(points.len() >= 3, "a polygon needs at least three points");
+        Polygon { points: points }
+    }
+
+    pub fn from_rect(rect: Rectangle) -> Polygon {
+        Polygon::new(vec![
+            (rect.x, rect.y),
+            (rect.x + rect.w, rect.y),
+            (rect.x + rect.w, rect.y + rect.h),
+            (rect.x, rect.y + rect.h),
+        ])
+    }
+
+    /// The same polygon, moved by the given offset.
+    pub fn translate(&self, dx: f64, dy: f64) -> Polygon {
+        Polygon::new(self.points.iter().map(|&(x, y)| (x + dx, y + dy)).collect())
+    }
+
+    pub fn bounds(&self) -> Rectangle {
+        let (mut min, mut max) = (self.points[0], self.points[0]);
+
+        for &(x, y) in &self.points[1..] {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+
+        Rectangle {
+            x: min.0,
+            y: min.1,
+            w: max.0 - min.0,
+            h: max.1 - min.1,
+        }
+    }
+
+    /// Tests for an overlap using the separating axis theorem: two convex
+    /// polygons are disjoint if and only if the normal of one of their edges
+    /// separates their projections.
+    pub fn overlaps(&self, other: &Polygon) -> bool {
+        self.axes().chain(other.axes()).all(|axis| {
+            let (a_min, a_max) = self.project(axis);
+            let (b_min, b_max) = other.project(axis);
+            a_min < b_max && b_min < a_max
+        })
+    }
+
+    pub fn overlaps_rect(&self, rect: Rectangle) -> bool {
+        self.overlaps(&Polygon::from_rect(rect))
+    }
+
+    pub fn overlaps_circle(&self, circle: Circle) -> bool {
+        // Besides the edge normals, the axis going through the center of the
+        // circle and the closest point of the polygon may separate them.
+        let distance = |&(x, y): &(f64, f64)| (x - circle.x).powi(2) + (y - circle.y).powi(2);
+        let closest = self.points[1..].iter().fold(self.points[0], |closest, point| {
+            if distance(point) < distance(&closest) { *point } else { closest }
+        });
+
+        let extra = normalize((closest.0 - circle.x, closest.1 - circle.y));
+
+        self.axes().chain(extra).all(|axis| {
+            let (min, max) = self.project(axis);
+            let center = circle.x * axis.0 + circle.y * axis.1;
+            min < center + circle.r && center - circle.r < max
+        })
+    }
+
+    /// The unit normals of the edges.
+    fn axes<'a>(&'a self) -> Box<dyn Iterator<Item = (f64, f64)> + 'a> {
+        let n = self.points.len();
+
+        Box::new((0..n).filter_map(move |i| {
+            let (a, b) = (self.points[i], self.points[(i + 1) % n]);
+            normalize((a.1 - b.1, b.0 - a.0))
+        }))
+    }
+
+    fn project(&self, axis: (f64, f64)) -> (f64, f64) {
+        self.points.iter().fold((::std::f64::INFINITY, ::std::f64::NEG_INFINITY), |(min, max), &(x, y)| {
+            let p = x * axis.0 + y * axis.1;
+            (min.min(p), max.max(p))
+        })
+    }
+}
+
+/// Returns `None` for vectors too short to have a direction.
+fn normalize(v: (f64, f64)) -> Option<(f64, f64)> {
+    let len = (v.0 * v.0 + v.1 * v.1).sqrt();
+
+    if len < 1e-9 {
+        None
+    } else {
+        Some((v.0 / len, v.1 / len))
+    }
+}
+
+/// Which pixels of an image are solid, for pixel-perfect collisions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlphaMask {
+    w: usize,
+    h: usize,
+    solid: Vec<bool>,
+}
+
+/// Pixels more transparent than this do not collide.
+const ALPHA_THRESHOLD: u8 = 128;
+
+impl AlphaMask {
+    /// Builds the mask of a region of an image, such as a frame of a sprite
+    /// sheet.
+    pub fn load(path: &str, region: Rectangle) -> Result<AlphaMask, String> {
+        let surface = Surface::from_file(path)?;
+        AlphaMask::from_surface(&surface, region)
+    }
+
+    pub fn from_surface(surface: &Surface, region: Rectangle) -> Result<AlphaMask, String> {
+        let (x0, y0) = (region.x as usize, region.y as usize);
+        let (w, h) = (region.w as usize, region.h as usize);
+
+        if x0 + w > surface.width() as usize || y0 + h > surface.height() as usize {
+            return Err(format!("the region {:?} is outside of the image", region));
+        }
+
+        let format = surface.pixel_format();
+        let bytes = surface.pixel_format_enum().byte_size_per_pixel();
+        let pitch = surface.pitch() as usize;
+
+        let solid = surface.with_lock(|pixels| {
+            let mut solid = Vec::with_capacity(w * h);
+
+            for y in y0..y0 + h {
+                for x in x0..x0 + w {
+                    let start = y * pitch + x * bytes;
+                    let pixel = read_pixel(&pixels[start..start + bytes]);
+                    let (_, _, _, alpha) = Color::from_u32(&format, pixel).rgba();
+                    solid.push(alpha >= ALPHA_THRESHOLD);
+                }
+            }
+
+            solid
+        });
+
+        Ok(AlphaMask {
+            w: w,
+            h: h,
+            solid: solid,
+        })
+    }
+
+    /// Whether the mask, stretched over `at`, has a solid pixel inside
+    /// `rect`.
+    pub fn overlaps_rect(&self, at: Rectangle, rect: Rectangle) -> bool {
+        let overlap = match at.intersection(rect) {
+            Some(overlap) => overlap,
+            None => return false,
+        };
+
+        let (sx, sy) = (self.w as f64 / at.w, self.h as f64 / at.h);
+        let left = ((overlap.x - at.x) * sx) as usize;
+        let top = ((overlap.y - at.y) * sy) as usize;
+        let right = (((overlap.x + overlap.w - at.x) * sx).ceil() as usize).min(self.w);
+        let bottom = (((overlap.y + overlap.h - at.y) * sy).ceil() as usize).min(self.h);
+
+        (top..bottom).any(|y| (left..right).any(|x| self.solid[y * self.w + x]))
+    }
+}
+
+/// Reads a pixel of up to four bytes, which SDL stores in the native byte
+/// order, so that the pixel format can then make sense of it.
+fn read_pixel(bytes: &[u8]) -> u32 {
+    let mut word = [0u8; 4];
+
+    if cfg!(target_endian = "big") {
+        word[4 - bytes.len()..].copy_from_slice(bytes);
+    } else {
+        word[..bytes.len()].copy_from_slice(bytes);
+    }
+
+    u32::from_ne_bytes(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    fn random_rects(rng: &mut XorShiftRng, count: usize, max_side: f64) -> Vec<Rectangle> {
+        (0..count).map(|_| Rectangle {
+            x: rng.gen_range(-200.0, 1400.0),
+            y: rng.gen_range(-200.0, 900.0),
+            w: rng.gen_range(1.0, max_side),
+            h: rng.gen_range(1.0, max_side),
+        }).collect()
+    }
+
+    fn hash(rects: &[Rectangle], cell_size: f64) -> SpatialHash {
+        let mut hash = SpatialHash::new(cell_size);
+        for (i, &rect) in rects.iter().enumerate() {
+            assert_eq!(hash.insert(rect), i);
+        }
+        hash
+    }
+
+    #[test]
+    fn spatial_hash_queries_like_brute_force() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let rects = random_rects(&mut rng, 300, 150.0);
+
+        // Cells smaller and larger than the rectangles.
+        for &cell_size in &[16.0, 96.0, 500.0] {
+            let hash = hash(&rects, cell_size);
+
+            for query in random_rects(&mut rng, 100, 200.0) {
+                let expected: Vec<usize> = (0..rects.len()).filter(|&i| rects[i].overlaps(query)).collect();
+                assert_eq!(hash.query(query), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn spatial_hash_pairs_cover_every_overlap() {
+        let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+        let rects = random_rects(&mut rng, 300, 150.0);
+
+        for &cell_size in &[16.0, 96.0, 500.0] {
+            let pairs = hash(&rects, cell_size).pairs();
+
+            let mut expected = vec![];
+            for a in 0..rects.len() {
+                for b in a + 1..rects.len() {
+                    if rects[a].overlaps(rects[b]) {
+                        expected.push((a, b));
+                    }
+                }
+            }
+
+            let overlapping: Vec<(usize, usize)> = pairs.iter()
+                .cloned()
+                .filter(|&(a, b)| rects[a].overlaps(rects[b]))
+                .collect();
+
+            assert_eq!(overlapping, expected);
+            assert!(pairs.iter().all(|&(a, b)| a < b));
+        }
+    }
+
+    #[test]
+    fn spatial_hash_forgets_cleared_rectangles() {
+        let rect = Rectangle { x: 10.0, y: 10.0, w: 20.0, h: 20.0 };
+        let mut hash = hash(&[rect, rect], 96.0);
+        assert_eq!(hash.pairs(), vec![(0, 1)]);
+
+        hash.clear();
+        assert_eq!(hash.query(rect), vec![]);
+        assert_eq!(hash.insert(rect), 0);
+        assert_eq!(hash.pairs(), vec![]);
+    }
+
+    #[test]
+    fn vectors_have_arithmetic() {
+        let v = Vec2::new(3.0, 4.0);
+
+        assert_eq!(v + Vec2::new(1.0, -1.0), Vec2::new(4.0, 3.0));
+        assert_eq!(v - v, Vec2::default());
+        assert_eq!(v * 2.0, Vec2::new(6.0, 8.0));
+        assert_eq!(v / 2.0, Vec2::new(1.5, 2.0));
+        assert_eq!(-v, Vec2::new(-3.0, -4.0));
+        assert_eq!(v.dot(Vec2::new(2.0, 1.0)), 10.0);
+        assert_eq!(v.length(), 5.0);
+        assert_eq!(v.normalized(), Vec2::new(0.6, 0.8));
+        assert_eq!(Vec2::default().normalized(), Vec2::default());
+
+        let mut w = v;
+        w += v;
+        w -= Vec2::new(1.0, 1.0);
+        assert_eq!(w, Vec2::new(5.0, 7.0));
+    }
+
+    #[test]
+    fn intersections_and_unions_contain_what_they_should() {
+        // Whole coordinates, so that sums are exact and rounding errors do not
+        // push the edges around.
+        let mut rng = XorShiftRng::from_seed([13, 14, 15, 16]);
+        let rects: Vec<Rectangle> = random_rects(&mut rng, 100, 300.0).into_iter().map(|rect| Rectangle {
+            x: rect.x.round(),
+            y: rect.y.round(),
+            w: rect.w.round(),
+            h: rect.h.round(),
+        }).collect();
+
+        for &a in &rects {
+            for &b in &rects {
+                let union = a.union(b);
+                assert!(union.contains(a) && union.contains(b));
+                assert_eq!(union, b.union(a));
+
+                match a.intersection(b) {
+                    Some(overlap) => {
+                        assert!(a.overlaps(b));
+                        assert!(a.contains(overlap) && b.contains(overlap));
+                        assert!(union.contains(overlap));
+                        assert!(overlap.w > 0.0 && overlap.h > 0.0);
+                    }
+                    None => assert!(!a.overlaps(b)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rectangles_side_by_side_do_not_overlap() {
+        let a = Rectangle { x: 0.0, y: 0.0, w: 10.0, h: 10.0 };
+        let b = Rectangle { x: 10.0, ..a };
+
+        assert!(!a.overlaps(b));
+        assert_eq!(a.intersection(b), None);
+        assert_eq!(a.union(b), Rectangle { w: 20.0, ..a });
+    }
+
+    #[test]
+    fn clamped_rectangles_stay_in_bounds() {
+        let mut rng = XorShiftRng::from_seed([17, 18, 19, 20]);
+        let bounds = Rectangle { x: 0.0, y: 0.0, w: 800.0, h: 600.0 };
+
+        for rect in random_rects(&mut rng, 500, 1000.0) {
+            let clamped = rect.clamp(bounds);
+            assert!(bounds.contains(clamped), "{:?} clamped to {:?}", rect, clamped);
+
+            match rect.move_inside(bounds) {
+                Some(moved) => {
+                    assert!(bounds.contains(moved));
+                    assert_eq!((moved.w, moved.h), (rect.w, rect.h));
+                    assert_eq!(moved, clamped);
+                }
+                None => assert!(rect.w > bounds.w || rect.h > bounds.h),
+            }
+        }
+    }
+
+    #[test]
+    fn rectangles_may_touch_the_far_edges() {
+        let bounds = Rectangle { x: 0.0, y: 0.0, w: 100.0, h: 100.0 };
+        let corner = Rectangle { x: 90.0, y: 80.0, w: 10.0, h: 20.0 };
+        assert_eq!(corner.move_inside(bounds), Some(corner));
+
+        let whole = bounds;
+        assert_eq!(whole.move_inside(bounds), Some(bounds));
+
+        let beyond = Rectangle { x: 95.0, ..corner };
+        assert_eq!(beyond.move_inside(bounds), Some(corner));
+        assert_eq!(Rectangle { w: 101.0, ..corner }.move_inside(bounds), None);
+    }
+
+    #[test]
+    fn rectangles_contain_their_near_edges_only() {
+        let rect = Rectangle { x: 10.0, y: 20.0, w: 30.0, h: 40.0 };
+
+        assert!(rect.contains_point(rect.center()));
+        assert!(rect.contains_point(Vec2::new(10.0, 20.0)));
+        assert!(rect.contains_point(Vec2::new(10.0, 50.0)));
+        assert!(rect.contains_point(Vec2::new(25.0, 20.0)));
+        assert!(rect.contains_point(Vec2::new(39.9, 59.9)));
+
+        assert!(!rect.contains_point(Vec2::new(40.0, 30.0)));
+        assert!(!rect.contains_point(Vec2::new(25.0, 60.0)));
+        assert!(!rect.contains_point(Vec2::new(40.0, 20.0)));
+        assert!(!rect.contains_point(Vec2::new(10.0, 60.0)));
+        assert!(!rect.contains_point(Vec2::new(40.0, 60.0)));
+        assert!(!rect.contains_point(Vec2::new(9.9, 30.0)));
+        assert!(!rect.contains_point(Vec2::new(25.0, 19.9)));
+    }
+
+    #[test]
+    fn rectangles_scale_about_their_center() {
+        let rect = Rectangle { x: 10.0, y: 20.0, w: 40.0, h: 60.0 };
+
+        assert_eq!(rect.scale(0.5), Rectangle { x: 20.0, y: 35.0, w: 20.0, h: 30.0 });
+        assert_eq!(rect.scale(2.0).center(), rect.center());
+        assert_eq!(rect.scale(1.0), rect);
+        assert_eq!(Rectangle::from_center(rect.center(), rect.w, rect.h), rect);
+        assert_eq!(rect.translate(Vec2::new(-10.0, 5.0)), Rectangle { x: 0.0, y: 25.0, ..rect });
+    }
+
+    #[test]
+    fn negative_sizes_are_flipped() {
+        let flipped = Rectangle { x: 50.0, y: 40.0, w: -20.0, h: -10.0 };
+        let rect = Rectangle { x: 30.0, y: 30.0, w: 20.0, h: 10.0 };
+
+        assert_eq!(flipped.normalized(), rect);
+        assert_eq!(flipped.to_sdl(), SdlRect::new(30, 30, 20, 10));
+        assert_eq!(rect.to_sdl(), SdlRect::new(30, 30, 20, 10));
+    }
+
+    #[test]
+    fn alpha_masks_only_collide_with_solid_pixels() {
+        // A 2x2 mask, solid on its top-right pixel, stretched over 20x20.
+        let mask = AlphaMask { w: 2, h: 2, solid: vec![false, true, false, false] };
+        let at = Rectangle { x: 100.0, y: 100.0, w: 20.0, h: 20.0 };
+
+        assert!(mask.overlaps_rect(at, Rectangle { x: 115.0, y: 95.0, w: 10.0, h: 10.0 }));
+        assert!(!mask.overlaps_rect(at, Rectangle { x: 95.0, y: 95.0, w: 10.0, h: 10.0 }));
+        assert!(!mask.overlaps_rect(at, Rectangle { x: 105.0, y: 112.0, w: 20.0, h: 20.0 }));
+        assert!(!mask.overlaps_rect(at, Rectangle { x: 200.0, y: 100.0, w: 10.0, h: 10.0 }));
+    }
+
+    fn triangle(points: [(f64, f64); 3]) -> Polygon {
+        Polygon::new(points.to_vec())
+    }
+
+    #[test]
+    fn reads_pixels_in_native_byte_order() {
+        for &pixel in &[0x0000_00ffu32, 0x1234_5678, 0xff00_0000] {
+            assert_eq!(read_pixel(&pixel.to_ne_bytes()), pixel);
+        }
+
+        let short = 0xf00fu16;
+        assert_eq!(read_pixel(&short.to_ne_bytes()), 0xf00f);
+        assert_eq!(read_pixel(&[0x42]), 0x42);
+    }
+
+    #[test]
+    fn polygons_overlap_when_no_axis_separates_them() {
+        let square = Polygon::from_rect(Rectangle { x: 0.0, y: 0.0, w: 10.0, h: 10.0 });
+
+        assert!(square.overlaps(&square.translate(5.0, 5.0)));
+        assert!(square.overlaps(&square.translate(-9.0, 0.0)));
+        // Edges which only touch do not overlap.
+        assert!(!square.overlaps(&square.translate(10.0, 0.0)));
+        assert!(!square.overlaps(&square.translate(0.0, -10.0)));
+
+        // A polygon inside the other.
+        let inner = triangle([(4.0, 4.0), (6.0, 4.0), (5.0, 6.0)]);
+        assert!(square.overlaps(&inner));
+        assert!(inner.overlaps(&square));
+    }
+
+    #[test]
+    fn polygons_are_separated_along_diagonals() {
+        // Their bounding boxes overlap, but the gap between their hypotenuses
+        // separates them.
+        let lower = triangle([(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)]);
+        let upper = triangle([(10.0, 1.0), (10.0, 11.0), (1.0, 11.0)]);
+
+        assert!(lower.bounds().overlaps(upper.bounds()));
+        assert!(!lower.overlaps(&upper));
+        assert!(!upper.overlaps(&lower));
+        assert!(lower.overlaps(&upper.translate(-2.0, -2.0)));
+    }
+
+    #[test]
+    fn polygons_overlap_rectangles() {
+        let diamond = Polygon::new(vec![(10.0, 0.0), (20.0, 10.0), (10.0, 20.0), (0.0, 10.0)]);
+
+        assert!(diamond.overlaps_rect(Rectangle { x: 8.0, y: 8.0, w: 4.0, h: 4.0 }));
+        // In the corner of the bounds, but outside of the diamond.
+        assert!(!diamond.overlaps_rect(Rectangle { x: 0.0, y: 0.0, w: 4.0, h: 4.0 }));
+    }
+
+    #[test]
+    fn polygons_overlap_circles() {
+        let square = Polygon::from_rect(Rectangle { x: 0.0, y: 0.0, w: 10.0, h: 10.0 });
+
+        assert!(square.overlaps_circle(Circle { x: 5.0, y: 5.0, r: 1.0 }));
+        assert!(square.overlaps_circle(Circle { x: 13.0, y: 5.0, r: 4.0 }));
+        assert!(!square.overlaps_circle(Circle { x: 15.0, y: 5.0, r: 4.0 }));
+
+        // Only the axis through the corner separates these two.
+        assert!(!square.overlaps_circle(Circle { x: 13.0, y: 13.0, r: 4.0 }));
+        assert!(square.overlaps_circle(Circle { x: 12.0, y: 12.0, r: 4.0 }));
+
+        // Circles which went wrong never overlap, rather than panicking.
+        let nan = ::std::f64::NAN;
+        assert!(!square.overlaps_circle(Circle { x: nan, y: 5.0, r: 4.0 }));
+        assert!(!square.overlaps_circle(Circle { x: 5.0, y: 5.0, r: nan }));
+    }
+
+    #[test]
+    fn circles_overlap_rectangles() {
+        let rect = Rectangle { x: 0.0, y: 0.0, w: 10.0, h: 10.0 };
+        let circle = Circle::inside(Rectangle { x: 0.0, y: 0.0, w: 20.0, h: 8.0 });
+        assert_eq!(circle, Circle { x: 10.0, y: 4.0, r: 4.0 });
+
+        assert!(Circle { x: 5.0, y: 5.0, r: 1.0 }.overlaps_rect(rect));
+        assert!(Circle { x: 12.0, y: 5.0, r: 3.0 }.overlaps_rect(rect));
+        assert!(!Circle { x: 13.0, y: 5.0, r: 3.0 }.overlaps_rect(rect));
+
+        // Near a corner, the distance counts rather than the bounds.
+        assert!(!Circle { x: 13.0, y: 13.0, r: 4.0 }.overlaps_rect(rect));
+        assert!(Circle { x: 12.0, y: 12.0, r: 3.0 }.overlaps_rect(rect));
+    }
+
+    /// Rectangles up to `side` pixels wide, spread over a square area which
+    /// grows with their amount, so that they are always as crowded.
+    fn scattered(rng: &mut XorShiftRng, count: usize, side: f64) -> Vec<Rectangle> {
+        let area = (count as f64).sqrt() * 40.0;
+
+        (0..count).map(|_| Rectangle {
+            x: rng.gen_range(0.0, area),
+            y: rng.gen_range(0.0, area),
+            w: rng.gen_range(1.0, side),
+            h: rng.gen_range(1.0, side),
+        }).collect()
+    }
+
+    #[test]
+    fn spatial_hash_candidates_grow_linearly() {
+        let mut rng = XorShiftRng::from_seed([9, 10, 11, 12]);
+        let few = scattered(&mut rng, 1_000, 16.0);
+        let many = scattered(&mut rng, 4_000, 16.0);
+
+        let few_pairs = hash(&few, 16.0).pairs().len();
+        let many_pairs = hash(&many, 16.0).pairs().len();
+
+        // Brute force tests every pair, about half a million of them for the
+        // first set, and sixteen times as many for four times the rectangles.
+        let brute_force = |n: usize| n * (n - 1) / 2;
+        assert!(few_pairs * 100 < brute_force(1_000), "{} candidate pairs", few_pairs);
+        assert!(many_pairs * 400 < brute_force(4_000), "{} candidate pairs", many_pairs);
+
+        // Just as crowded, four times as many rectangles give about four times
+        // as many candidates.
+        let growth = many_pairs as f64 / few_pairs as f64;
+        assert!(growth > 3.0 && growth < 5.0, "candidates grew {} times", growth);
+    }
 }
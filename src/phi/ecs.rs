@@ -0,0 +1,793 @@
+//! A small entity-component system.
+//!
+//! Entities are plain identifiers. Their data lives in components, stored per
+//! type, and their behaviour in systems which run over every entity having
+//! the components they need:
+//!
+//! ```ignore
+//! let mut world = World::new();
+//! let explosion = world.spawn();
+//! world.insert(explosion, Body(rect));
+//! world.insert(explosion, Lifetime(1.0));
+//!
+//! let mut schedule = Schedule::new().with(movement).with(aging);
+//! schedule.run(&mut world, phi, dt);
+//! ```
+//!
+//! The game-agnostic components and systems are defined at the end of this
+//! module; games add their own as needed.
+
+use phi::Phi;
+use phi::data::{AlphaMask, Circle, Polygon, Rectangle, SpatialHash, Vec2};
+use phi::gfx::{AnimatedSprite, CopySprite, Sprite};
+use phi::path::Path;
+use sdl2::pixels::Color;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Identifies an entity. Identifiers of despawned entities are reused, so the
+/// generation tells apart the different entities living at the same index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: usize,
+    generation: u32,
+}
+
+/// Type-erased access to the storage of one type of component.
+trait Storage {
+    fn remove(&mut self, index: usize);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> Storage for Vec<Option<T>> {
+    fn remove(&mut self, index: usize) {
+        if let Some(slot) = self.get_mut(index) {
+            *slot = None;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Holds every entity, their components, and resources shared between
+/// systems.
+pub struct World {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<usize>,
+    components: HashMap<TypeId, Box<dyn Storage>>,
+    resources: HashMap<TypeId, Box<dyn Any>>,
+    // Entities to despawn once the current systems are done running.
+    doomed: Vec<Entity>,
+}
+
+impl World {
+    pub fn new() -> World {
+        World {
+            generations: vec![],
+            alive: vec![],
+            free: vec![],
+            components: HashMap::new(),
+            resources: HashMap::new(),
+            doomed: vec![],
+        }
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        match self.free.pop() {
+            Some(index) => {
+                self.alive[index] = true;
+                Entity {
+                    index: index,
+                    generation: self.generations[index],
+                }
+            }
+            None => {
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity {
+                    index: self.alive.len() - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Marks an entity to be removed, along with its components, the next
+    /// time the world is maintained. Systems can thus despawn entities while
+    /// others are still looking at them.
+    pub fn despawn(&mut self, entity: Entity) {
+        if self.is_alive(entity) && !self.doomed.contains(&entity) {
+            self.doomed.push(entity);
+        }
+    }
+
+    /// Removes the entities which were despawned.
+    pub fn maintain(&mut self) {
+        for entity in ::std::mem::replace(&mut self.doomed, vec![]) {
+            if !self.is_alive(entity) {
+                continue;
+            }
+
+            for storage in self.components.values_mut() {
+                storage.remove(entity.index);
+            }
+
+            self.alive[entity.index] = false;
+            self.generations[entity.index] += 1;
+            self.free.push(entity.index);
+        }
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.alive.get(entity.index).cloned().unwrap_or(false) &&
+        self.generations[entity.index] == entity.generation
+    }
+
+    /// Adds a component to an entity, replacing the previous component of the
+    /// same type.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) {
+        if !self.is_alive(entity) {
+            return;
+        }
+
+        let storage = self.storage_mut::<T>();
+        while storage.len() <= entity.index {
+            storage.push(None);
+        }
+
+        storage[entity.index] = Some(component);
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        self.storage_mut::<T>().get_mut(entity.index).and_then(Option::take)
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        self.storage::<T>()
+            .and_then(|storage| storage.get(entity.index))
+            .and_then(Option::as_ref)
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        self.storage_mut::<T>().get_mut(entity.index).and_then(Option::as_mut)
+    }
+
+    /// Every entity having a component of type `T`, oldest first.
+    pub fn with<T: 'static>(&self) -> Vec<Entity> {
+        let storage = match self.storage::<T>() {
+            Some(storage) => storage,
+            None => return vec![],
+        };
+
+        storage.iter().enumerate()
+            .filter(|&(index, component)| component.is_some() && self.alive[index])
+            .map(|(index, _)| Entity {
+                index: index,
+                generation: self.generations[index],
+            })
+            .collect()
+    }
+
+    /// Stores a value shared by systems, rather than attached to an entity.
+    pub fn insert_resource<T: 'static>(&mut self, resource: T) {
+        self.resources.insert(TypeId::of::<T>(), Box::new(resource));
+    }
+
+    pub fn resource<T: 'static>(&self) -> Option<&T> {
+        self.resources.get(&TypeId::of::<T>()).and_then(|resource| resource.downcast_ref())
+    }
+
+    fn storage<T: 'static>(&self) -> Option<&Vec<Option<T>>> {
+        self.components.get(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any().downcast_ref())
+    }
+
+    fn storage_mut<T: 'static>(&mut self) -> &mut Vec<Option<T>> {
+        self.components.entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Vec::<Option<T>>::new()))
+            .as_any_mut()
+            .downcast_mut()
+            .unwrap()
+    }
+}
+
+/// Behaviour applied to the entities of a world.
+pub trait System {
+    fn run(&mut self, world: &mut World, phi: &mut Phi, dt: f64);
+}
+
+impl<F: FnMut(&mut World, &mut Phi, f64)> System for F {
+    fn run(&mut self, world: &mut World, phi: &mut Phi, dt: f64) {
+        self(world, phi, dt)
+    }
+}
+
+/// Runs systems in the order in which they were added.
+pub struct Schedule {
+    systems: Vec<Box<dyn System>>,
+}
+
+impl Schedule {
+    pub fn new() -> Schedule {
+        Schedule { systems: vec![] }
+    }
+
+    pub fn with<S: System + 'static>(mut self, system: S) -> Schedule {
+        self.systems.push(Box::new(system));
+        self
+    }
+
+    /// Runs every system, then removes the entities they despawned.
+    pub fn run(&mut self, world: &mut World, phi: &mut Phi, dt: f64) {
+        for system in &mut self.systems {
+            system.run(world, phi, dt);
+        }
+
+        world.maintain();
+    }
+}
+
+
+// Components
+
+/// The area covered by an entity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Body(pub Rectangle);
+
+/// In pixels per second.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Velocity(pub Vec2);
+
+/// The time left, in seconds, before the entity is despawned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lifetime(pub f64);
+
+/// Despawns the entity once it has left the screen, unless its velocity
+/// brings it back, as for entities spawned just beyond an edge.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Offscreen;
+
+/// Moves the body of an entity along a path starting at `origin`, its
+/// top-left corner, then despawns it once the path is over.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FollowPath {
+    pub origin: Vec2,
+    pub path: Path,
+    // For how long the entity has been following its path.
+    time: f64,
+}
+
+impl FollowPath {
+    pub fn new(origin: Vec2, path: Path) -> FollowPath {
+        FollowPath {
+            origin: origin,
+            path: path,
+            time: 0.0,
+        }
+    }
+}
+
+/// Makes an entity show up in the `Contacts` of other entities.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Collider {
+    /// The layers, as bits, which the entity is part of.
+    pub layers: u32,
+    /// The layers, as bits, which the entity wants to know it touched.
+    pub collides_with: u32,
+}
+
+/// The part of its body with which an entity collides. Entities without a
+/// shape collide with the whole of their body.
+#[derive(Clone)]
+pub enum Shape {
+    Rect,
+    /// A circle at the center of the body, its radius being a fraction of the
+    /// width of the body.
+    Circle(f64),
+    /// A convex outline, relative to the top-left corner of the body.
+    Polygon(Polygon),
+    /// The solid pixels of the image drawn over the body.
+    Mask(Rc<AlphaMask>),
+    /// Several rectangles, relative to the top-left corner of the body.
+    Boxes(Vec<Rectangle>),
+}
+
+impl Shape {
+    /// Whether the shape, over a body at `at`, overlaps `other` over a body at
+    /// `other_at`. Shapes which cannot be compared exactly, such as masks,
+    /// are compared with the bounds of the other shape.
+    pub fn overlaps(&self, at: Rectangle, other: &Shape, other_at: Rectangle) -> bool {
+        let offset = Vec2::new(at.x, at.y);
+
+        match (self, other) {
+            (&Shape::Rect, _) => other.overlaps_rect(other_at, at),
+            (_, &Shape::Rect) => self.overlaps_rect(at, other_at),
+            (&Shape::Boxes(ref boxes), _) => {
+                boxes.iter().any(|rect| other.overlaps_rect(other_at, rect.translate(offset)))
+            }
+            (_, &Shape::Boxes(_)) => other.overlaps(other_at, self, at),
+            (&Shape::Circle(radius), &Shape::Circle(other_radius)) => {
+                let (a, b) = (circle(at, radius), circle(other_at, other_radius));
+                (a.x - b.x).powi(2) + (a.y - b.y).powi(2) < (a.r + b.r).powi(2)
+            }
+            (&Shape::Polygon(ref polygon), &Shape::Circle(radius)) => {
+                polygon.translate(at.x, at.y).overlaps_circle(circle(other_at, radius))
+            }
+            (&Shape::Circle(_), &Shape::Polygon(_)) => other.overlaps(other_at, self, at),
+            (&Shape::Polygon(ref polygon), &Shape::Polygon(ref other_polygon)) => {
+                polygon.translate(at.x, at.y).overlaps(&other_polygon.translate(other_at.x, other_at.y))
+            }
+            _ => self.overlaps_rect(at, other.bounds(other_at)) && other.overlaps_rect(other_at, self.bounds(at)),
+        }
+    }
+
+    fn overlaps_rect(&self, at: Rectangle, rect: Rectangle) -> bool {
+        match *self {
+            Shape::Rect => at.overlaps(rect),
+            Shape::Circle(radius) => circle(at, radius).overlaps_rect(rect),
+            Shape::Polygon(ref polygon) => polygon.translate(at.x, at.y).overlaps_rect(rect),
+            Shape::Mask(ref mask) => mask.overlaps_rect(at, rect),
+            Shape::Boxes(ref boxes) => {
+                boxes.iter().any(|hitbox| hitbox.translate(Vec2::new(at.x, at.y)).overlaps(rect))
+            }
+        }
+    }
+
+    /// The smallest rectangle containing the shape.
+    fn bounds(&self, at: Rectangle) -> Rectangle {
+        match *self {
+            Shape::Rect | Shape::Mask(_) => at,
+            Shape::Circle(radius) => {
+                let circle = circle(at, radius);
+                Rectangle::from_center(Vec2::new(circle.x, circle.y), circle.r * 2.0, circle.r * 2.0)
+            }
+            Shape::Polygon(ref polygon) => polygon.translate(at.x, at.y).bounds(),
+            Shape::Boxes(ref boxes) => {
+                let offset = Vec2::new(at.x, at.y);
+                match boxes.split_first() {
+                    Some((first, rest)) => rest.iter()
+                        .fold(first.translate(offset), |bounds, hitbox| bounds.union(hitbox.translate(offset))),
+                    None => Rectangle { w: 0.0, h: 0.0, ..at },
+                }
+            }
+        }
+    }
+}
+
+fn circle(at: Rectangle, radius: f64) -> Circle {
+    Circle {
+        r: at.w * radius,
+        ..Circle::inside(at)
+    }
+}
+
+/// How an entity is drawn over its body.
+#[derive(Clone)]
+pub enum Drawable {
+    Sprite(Sprite),
+    Animated(AnimatedSprite),
+    /// A plain rectangle, optionally with a border of another color and of
+    /// the given thickness.
+    Rect { color: Color, border: Option<(Color, f64)> },
+}
+
+/// Entities having a higher order are drawn over the others. Entities
+/// without one are at 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ZOrder(pub i32);
+
+/// The pairs of entities which touched during the last run of `Collisions`,
+/// as `(a, b)` where `a` collides with the layers of `b`.
+pub struct Contacts(pub Vec<(Entity, Entity)>);
+
+
+// Systems
+
+/// Moves bodies according to their velocity.
+pub fn movement(world: &mut World, _: &mut Phi, dt: f64) {
+    for entity in world.with::<Velocity>() {
+        let Velocity(vel) = *world.get::<Velocity>(entity).unwrap();
+
+        if let Some(&mut Body(ref mut rect)) = world.get_mut::<Body>(entity) {
+            *rect = rect.translate(vel * dt);
+        }
+    }
+}
+
+/// Counts down lifetimes, advances animations, and despawns what expired or
+/// left the screen.
+pub fn aging(world: &mut World, phi: &mut Phi, dt: f64) {
+    for entity in world.with::<Drawable>() {
+        if let Some(&mut Drawable::Animated(ref mut sprite)) = world.get_mut::<Drawable>(entity) {
+            sprite.add_time(dt);
+        }
+    }
+
+    for entity in world.with::<Lifetime>() {
+        let left = {
+            let lifetime = world.get_mut::<Lifetime>(entity).unwrap();
+            lifetime.0 -= dt;
+            lifetime.0
+        };
+
+        if left <= 0.0 {
+            world.despawn(entity);
+        }
+    }
+
+    let (w, h) = phi.output_size();
+    let screen = Rectangle { x: 0.0, y: 0.0, w: w, h: h };
+
+    for entity in world.with::<Offscreen>() {
+        let vel = world.get::<Velocity>(entity).map_or(Vec2::default(), |&Velocity(vel)| vel);
+
+        if let Some(&Body(rect)) = world.get::<Body>(entity) {
+            if has_left(screen, rect, vel) {
+                world.despawn(entity);
+            }
+        }
+    }
+}
+
+/// Whether `rect` is beyond the edges of `screen`, and not moving back
+/// towards it along every axis it is beyond.
+fn has_left(screen: Rectangle, rect: Rectangle, vel: Vec2) -> bool {
+    let returning = |before: bool, after: bool, speed: f64| {
+        (!before && !after) || (before && speed > 0.0) || (after && speed < 0.0)
+    };
+
+    !screen.overlaps(rect) &&
+    !(returning(rect.x + rect.w <= screen.x, rect.x >= screen.x + screen.w, vel.x) &&
+      returning(rect.y + rect.h <= screen.y, rect.y >= screen.y + screen.h, vel.y))
+}
+
+/// Moves entities along their path, and despawns them once it is over.
+pub fn paths(world: &mut World, _: &mut Phi, dt: f64) {
+    follow_paths(world, dt);
+}
+
+fn follow_paths(world: &mut World, dt: f64) {
+    for entity in world.with::<FollowPath>() {
+        let offset = {
+            let follow = world.get_mut::<FollowPath>(entity).unwrap();
+            follow.time += dt;
+            follow.path.offset_at(follow.time).map(|(dx, dy)| follow.origin + Vec2::new(dx, dy))
+        };
+
+        match offset {
+            Some(at) => if let Some(&mut Body(ref mut rect)) = world.get_mut::<Body>(entity) {
+                rect.x = at.x;
+                rect.y = at.y;
+            },
+            None => world.despawn(entity),
+        }
+    }
+}
+
+/// Fills the `Contacts` resource with the colliders touching each other.
+pub struct Collisions {
+    grid: SpatialHash,
+}
+
+impl Collisions {
+    /// Cells should be about the size of the larger colliders.
+    pub fn new(cell_size: f64) -> Collisions {
+        Collisions { grid: SpatialHash::new(cell_size) }
+    }
+
+    /// Finds the contacts, as `run` does.
+    pub fn detect(&mut self, world: &mut World) {
+        let colliders: Vec<(Entity, Collider, Rectangle)> = world.with::<Collider>().into_iter()
+            .filter_map(|entity| {
+                let collider = *world.get::<Collider>(entity).unwrap();
+                world.get::<Body>(entity).map(|&Body(rect)| (entity, collider, rect))
+            })
+            .collect();
+
+        self.grid.clear();
+        for &(_, _, rect) in &colliders {
+            self.grid.insert(rect);
+        }
+
+        let whole = Shape::Rect;
+        let shape = |entity| world.get::<Shape>(entity).unwrap_or(&whole);
+
+        let mut contacts = vec![];
+        for (i, j) in self.grid.pairs() {
+            let (a, b) = (colliders[i], colliders[j]);
+            let wanted = a.1.collides_with & b.1.layers != 0 || b.1.collides_with & a.1.layers != 0;
+
+            if !wanted || !a.2.overlaps(b.2) || !shape(a.0).overlaps(a.2, shape(b.0), b.2) {
+                continue;
+            }
+
+            if a.1.collides_with & b.1.layers != 0 {
+                contacts.push((a.0, b.0));
+            }
+
+            if b.1.collides_with & a.1.layers != 0 {
+                contacts.push((b.0, a.0));
+            }
+        }
+
+        world.insert_resource(Contacts(contacts));
+    }
+}
+
+impl System for Collisions {
+    fn run(&mut self, world: &mut World, _: &mut Phi, _: f64) {
+        self.detect(world);
+    }
+}
+
+/// Draws every drawable entity, by order, then by index.
+pub fn render(world: &mut World, phi: &mut Phi, _: f64) {
+    let mut drawables = world.with::<Drawable>();
+    drawables.sort_by_key(|&entity| world.get::<ZOrder>(entity).cloned().unwrap_or(ZOrder(0)));
+
+    for entity in drawables {
+        let rect = match world.get::<Body>(entity) {
+            Some(&Body(rect)) => rect,
+            None => continue,
+        };
+
+        match *world.get::<Drawable>(entity).unwrap() {
+            Drawable::Sprite(ref sprite) => phi.renderer.copy_sprite(sprite, rect),
+            Drawable::Animated(ref sprite) => phi.renderer.copy_sprite(sprite, rect),
+            Drawable::Rect { color, border } => {
+                let inner = match border {
+                    Some((border_color, thickness)) => {
+                        phi.renderer.set_draw_color(border_color);
+                        let _ = phi.renderer.fill_rect(rect.to_sdl());
+
+                        Rectangle {
+                            x: rect.x + thickness,
+                            y: rect.y + thickness,
+                            w: rect.w - thickness * 2.0,
+                            h: rect.h - thickness * 2.0,
+                        }
+                    }
+                    None => rect,
+                };
+
+                phi.renderer.set_draw_color(color);
+                let _ = phi.renderer.fill_rect(inner.to_sdl());
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64) -> Body {
+        Body(Rectangle { x: x, y: y, w: 10.0, h: 10.0 })
+    }
+
+    #[test]
+    fn reuses_indices_with_a_new_generation() {
+        let mut world = World::new();
+        let first = world.spawn();
+        world.insert(first, Lifetime(1.0));
+
+        world.despawn(first);
+        world.maintain();
+
+        let second = world.spawn();
+        assert_eq!(second.index, first.index);
+        assert_ne!(second, first);
+        assert!(world.is_alive(second));
+        assert!(!world.is_alive(first));
+
+        // Components of the previous entity are gone, and it cannot be
+        // modified through its stale identifier.
+        assert_eq!(world.get::<Lifetime>(second), None);
+        world.insert(first, Lifetime(2.0));
+        assert_eq!(world.get::<Lifetime>(second), None);
+        assert_eq!(world.get::<Lifetime>(first), None);
+    }
+
+    #[test]
+    fn despawns_once_maintained() {
+        let mut world = World::new();
+        let doomed = world.spawn();
+        let survivor = world.spawn();
+
+        for &entity in &[doomed, survivor] {
+            world.insert(entity, Lifetime(1.0));
+            world.insert(entity, Offscreen);
+        }
+
+        world.despawn(doomed);
+        world.despawn(doomed);
+
+        // Systems running after the despawn still see the entity.
+        assert!(world.is_alive(doomed));
+        assert_eq!(world.with::<Lifetime>(), vec![doomed, survivor]);
+
+        world.maintain();
+        assert!(!world.is_alive(doomed));
+        assert_eq!(world.get::<Lifetime>(doomed), None);
+        assert_eq!(world.with::<Lifetime>(), vec![survivor]);
+        assert_eq!(world.with::<Offscreen>(), vec![survivor]);
+
+        // Despawned twice, but only freed once.
+        let (a, b) = (world.spawn(), world.spawn());
+        assert_eq!(a.index, doomed.index);
+        assert_eq!(b.index, 2);
+    }
+
+    #[test]
+    fn replaces_components_and_resources() {
+        let mut world = World::new();
+        let entity = world.spawn();
+
+        world.insert(entity, Lifetime(1.0));
+        world.get_mut::<Lifetime>(entity).unwrap().0 += 1.0;
+        assert_eq!(world.get::<Lifetime>(entity), Some(&Lifetime(2.0)));
+
+        world.insert(entity, Lifetime(5.0));
+        assert_eq!(world.get::<Lifetime>(entity), Some(&Lifetime(5.0)));
+
+        assert_eq!(world.remove::<Lifetime>(entity), Some(Lifetime(5.0)));
+        assert_eq!(world.remove::<Lifetime>(entity), None);
+        assert_eq!(world.with::<Lifetime>(), vec![]);
+
+        assert!(world.resource::<Contacts>().is_none());
+        world.insert_resource(Contacts(vec![(entity, entity)]));
+        world.insert_resource(Contacts(vec![]));
+        assert_eq!(world.resource::<Contacts>().unwrap().0, vec![]);
+    }
+
+    #[test]
+    fn reports_contacts_by_layer() {
+        let mut world = World::new();
+        let ship = world.spawn();
+        let near = world.spawn();
+        let far = world.spawn();
+        let ghost = world.spawn();
+
+        world.insert(ship, rect(0.0, 0.0));
+        world.insert(ship, Collider { layers: 0, collides_with: 1 });
+
+        for &(entity, x) in &[(near, 5.0), (far, 500.0)] {
+            world.insert(entity, rect(x, 5.0));
+            world.insert(entity, Collider { layers: 1, collides_with: 0 });
+        }
+
+        // Overlaps the ship, but on a layer it does not care about.
+        world.insert(ghost, rect(2.0, 2.0));
+        world.insert(ghost, Collider { layers: 2, collides_with: 0 });
+
+        let mut collisions = Collisions::new(16.0);
+        collisions.detect(&mut world);
+        assert_eq!(world.resource::<Contacts>().unwrap().0, vec![(ship, near)]);
+
+        // The grid is rebuilt from scratch every time.
+        world.insert(ship, rect(495.0, 0.0));
+        collisions.detect(&mut world);
+        assert_eq!(world.resource::<Contacts>().unwrap().0, vec![(ship, far)]);
+    }
+
+    #[test]
+    fn narrows_contacts_down_to_shapes() {
+        let mut world = World::new();
+        let ship = world.spawn();
+        let rock = world.spawn();
+        let turret = world.spawn();
+
+        // A triangle pointing right, which leaves the top-right corner of its
+        // body empty.
+        world.insert(ship, Body(Rectangle { x: 0.0, y: 0.0, w: 40.0, h: 40.0 }));
+        world.insert(ship, Shape::Polygon(Polygon::new(vec![(0.0, 0.0), (40.0, 20.0), (0.0, 40.0)])));
+        world.insert(ship, Collider { layers: 0, collides_with: 1 });
+
+        // Only the corner of the body of the rock overlaps the ship.
+        world.insert(rock, Body(Rectangle { x: 35.0, y: -35.0, w: 40.0, h: 40.0 }));
+        world.insert(rock, Shape::Circle(0.5));
+        world.insert(rock, Collider { layers: 1, collides_with: 0 });
+
+        // The hitboxes are apart, and only the lower one reaches the tip of
+        // the ship.
+        world.insert(turret, Body(Rectangle { x: 30.0, y: 0.0, w: 40.0, h: 40.0 }));
+        world.insert(turret, Shape::Boxes(vec![
+            Rectangle { x: 30.0, y: 0.0, w: 10.0, h: 10.0 },
+            Rectangle { x: 5.0, y: 15.0, w: 10.0, h: 10.0 },
+        ]));
+        world.insert(turret, Collider { layers: 1, collides_with: 0 });
+
+        let mut collisions = Collisions::new(64.0);
+        collisions.detect(&mut world);
+        assert_eq!(world.resource::<Contacts>().unwrap().0, vec![(ship, turret)]);
+
+        // Without the lower hitbox, the body of the turret still overlaps
+        // the ship, but none of its shape does.
+        world.insert(turret, Shape::Boxes(vec![Rectangle { x: 30.0, y: 0.0, w: 10.0, h: 10.0 }]));
+        world.insert(rock, Body(Rectangle { x: 20.0, y: -20.0, w: 40.0, h: 40.0 }));
+        collisions.detect(&mut world);
+        assert_eq!(world.resource::<Contacts>().unwrap().0, vec![(ship, rock)]);
+    }
+
+    #[test]
+    fn shapes_compare_with_each_other_either_way() {
+        let at = Rectangle { x: 0.0, y: 0.0, w: 20.0, h: 20.0 };
+        let near = Rectangle { x: 18.0, y: 0.0, w: 20.0, h: 20.0 };
+        let far = Rectangle { x: 18.0, y: 18.0, w: 20.0, h: 20.0 };
+        let shapes = [
+            Shape::Rect,
+            Shape::Circle(0.5),
+            Shape::Polygon(Polygon::from_rect(Rectangle { x: 0.0, y: 0.0, w: 20.0, h: 20.0 })),
+            Shape::Boxes(vec![Rectangle { x: 0.0, y: 0.0, w: 20.0, h: 20.0 }]),
+        ];
+
+        for a in &shapes {
+            for b in &shapes {
+                assert!(a.overlaps(at, b, near) && b.overlaps(near, a, at));
+            }
+        }
+
+        // Rounded shapes miss the corners of squares.
+        assert!(!Shape::Circle(0.5).overlaps(at, &Shape::Rect, far));
+        assert!(!Shape::Rect.overlaps(far, &Shape::Circle(0.5), at));
+        assert!(Shape::Rect.overlaps(at, &shapes[2], far));
+    }
+
+    #[test]
+    fn offscreen_entities_may_come_back() {
+        let screen = Rectangle { x: 0.0, y: 0.0, w: 100.0, h: 100.0 };
+        let beyond_right = Rectangle { x: 100.0, y: 50.0, w: 10.0, h: 10.0 };
+        let above = Rectangle { x: 50.0, y: -10.0, w: 10.0, h: 10.0 };
+
+        assert!(!has_left(screen, beyond_right, Vec2::new(-1.0, 0.0)));
+        assert!(has_left(screen, beyond_right, Vec2::new(1.0, 0.0)));
+        assert!(has_left(screen, beyond_right, Vec2::default()));
+        assert!(!has_left(screen, above, Vec2::new(-1.0, 1.0)));
+        assert!(has_left(screen, above, Vec2::new(0.0, -1.0)));
+
+        // Coming back along one axis only is not enough.
+        let corner = Rectangle { x: -10.0, y: -10.0, w: 10.0, h: 10.0 };
+        assert!(has_left(screen, corner, Vec2::new(1.0, 0.0)));
+        assert!(!has_left(screen, corner, Vec2::new(1.0, 1.0)));
+
+        assert!(!has_left(screen, Rectangle { x: 95.0, ..beyond_right }, Vec2::new(1.0, 0.0)));
+    }
+
+    #[test]
+    fn follows_paths_then_despawns() {
+        let mut world = World::new();
+        let entity = world.spawn();
+        world.insert(entity, rect(0.0, 0.0));
+        world.insert(entity, FollowPath::new(Vec2::new(100.0, 50.0), Path::new().line(-20.0, 10.0, 2.0)));
+
+        follow_paths(&mut world, 1.0);
+        world.maintain();
+        assert_eq!(world.get::<Body>(entity), Some(&rect(90.0, 55.0)));
+
+        follow_paths(&mut world, 1.0);
+        world.maintain();
+        assert!(!world.is_alive(entity));
+    }
+}
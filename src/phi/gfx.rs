@@ -2,6 +2,7 @@ use phi::data::Rectangle;
 use std::cell::RefCell;
 use std::path::Path;
 use std::rc::Rc;
+use sdl2::pixels::Color;
 use sdl2::render::{Renderer, Texture};
 use sdl2::image::LoadTexture;
 
@@ -144,4 +145,19 @@ impl Sprite {
                       Option::from(self.src.to_sdl()),
                       Option::from(dest.to_sdl()));
     }
+
+    /// Renders the sprite with its colors multiplied by `tint`, alpha
+    /// included.
+    pub fn render_tinted(&self, renderer: &mut Renderer, dest: Rectangle, tint: Color) {
+        let mut tex = self.tex.borrow_mut();
+        let (r, g, b, a) = tint.rgba();
+
+        tex.set_color_mod(r, g, b);
+        tex.set_alpha_mod(a);
+        let _ = renderer.copy(&mut tex, Option::from(self.src.to_sdl()), Option::from(dest.to_sdl()));
+
+        // The texture is shared with other sprites.
+        tex.set_color_mod(255, 255, 255);
+        tex.set_alpha_mod(255);
+    }
 }
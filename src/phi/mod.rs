@@ -1,9 +1,17 @@
 #[macro_use]
 mod events;
+pub mod audio;
+pub mod clock;
 pub mod data;
+pub mod ecs;
 pub mod gfx;
+pub mod particles;
+pub mod path;
+pub mod tween;
 
 use sdl2::render::Renderer;
+use self::audio::Audio;
+use self::clock::Clock;
 use self::gfx::Sprite;
 use sdl2::pixels::Color;
 use std::collections::HashMap;
@@ -27,15 +35,20 @@ struct_events! {
 pub struct Phi<'a> {
     pub events: Events,
     pub renderer: Renderer<'a>,
+    pub audio: Audio,
+    /// The time of the game, as opposed to the real time.
+    pub clock: Clock,
     cached_fonts: HashMap<(&'static str, u16), ::sdl2::ttf::Font<'static, 'static>>,
     font_context: Sdl2TtfContext,
 }
 
 impl<'a> Phi<'a> {
-    pub fn new(events: Events, renderer: Renderer<'a>, font_context: Sdl2TtfContext) -> Phi<'a> {
+    pub fn new(events: Events, renderer: Renderer<'a>, audio: Audio, font_context: Sdl2TtfContext) -> Phi<'a> {
         Phi {
             events: events,
             renderer: renderer,
+            audio: audio,
+            clock: Clock::new(),
             cached_fonts: HashMap::new(),
             font_context: font_context,
         }
@@ -59,7 +72,7 @@ impl<'a> Phi<'a> {
 pub enum ViewAction {
     None,
     Quit,
-    ChangeView(Box<View>),
+    ChangeView(Box<dyn View>),
 }
 
 pub trait View {
@@ -67,10 +80,11 @@ pub trait View {
 }
 
 pub fn spawn<F>(title: &str, init: F)
-    where F: Fn(&mut Phi) -> Box<View>
+    where F: Fn(&mut Phi) -> Box<dyn View>
 {
     let sdl_context = ::sdl2::init().unwrap();
     let video = sdl_context.video().unwrap();
+    let _audio = sdl_context.audio().ok();
     let mut timer = sdl_context.timer().unwrap();
     let _image_context = ::sdl2::image::init(::sdl2::image::INIT_PNG).unwrap();
     let ttf_context = ::sdl2::ttf::init().unwrap();
@@ -84,6 +98,7 @@ pub fn spawn<F>(title: &str, init: F)
 
     let mut context = Phi::new(Events::new(sdl_context.event_pump().unwrap()),
                                window.renderer().accelerated().build().unwrap(),
+                               Audio::open(),
                                ttf_context
                       );
 
@@ -114,11 +129,19 @@ pub fn spawn<F>(title: &str, init: F)
         }
 
         context.events.pump(&mut context.renderer);
+        context.audio.update(elapsed);
 
-        match current_view.render(&mut context, elapsed) {
+        // Views are handed the time elapsed in the game, and can query the
+        // clock for the real time.
+        let game_elapsed = context.clock.tick(elapsed);
+
+        match current_view.render(&mut context, game_elapsed) {
             ViewAction::None => context.renderer.present(),
             ViewAction::Quit => break,
-            ViewAction::ChangeView(new_view) => current_view = new_view,
+            ViewAction::ChangeView(new_view) => {
+                context.clock.reset();
+                current_view = new_view;
+            }
         }
     }
 }
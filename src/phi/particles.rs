@@ -0,0 +1,344 @@
+//! Particles are small, short-lived and purely cosmetic, so they use their own
+//! random numbers and never affect gameplay.
+
+use phi::Phi;
+use phi::data::{Rectangle, Vec2};
+use phi::gfx::Sprite;
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+use std::f64::consts::PI;
+
+/// A spread sending particles in every direction.
+pub const ALL_AROUND: f64 = PI;
+
+/// Describes how the particles of an emitter look and behave.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleConfig {
+    /// The range, in seconds, from which the lifetime of every particle is
+    /// picked.
+    pub lifetime: (f64, f64),
+    /// The range, in pixels per second, of the initial speed.
+    pub speed: (f64, f64),
+    /// The average direction of the particles, in radians.
+    pub direction: f64,
+    /// How far particles may stray from `direction`, in radians on both
+    /// sides. A spread of PI sends them in every direction.
+    pub spread: f64,
+    /// Added to the velocity every second.
+    pub gravity: Vec2,
+    /// The fraction of their velocity particles lose every second.
+    pub drag: f64,
+    /// Particles fade from the first color to the second over their lifetime,
+    /// alpha included.
+    pub colors: (Color, Color),
+    /// Particles grow or shrink from the first size to the second.
+    pub sizes: (f64, f64),
+}
+
+/// How an emitter produces particles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Emission {
+    /// Only emits when asked to through `Emitter::burst`.
+    Burst,
+    /// Emits the given number of particles per second while active.
+    Continuous(f64),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Particle {
+    pos: Vec2,
+    vel: Vec2,
+    age: f64,
+    lifetime: f64,
+}
+
+/// A fixed amount of particles, whose slots are reused once they die so that
+/// emitting never allocates.
+struct Pool {
+    particles: Vec<Particle>,
+    // Whether each slot holds a live particle.
+    alive: Vec<bool>,
+    free: Vec<usize>,
+}
+
+impl Pool {
+    fn with_capacity(capacity: usize) -> Pool {
+        let dead = Particle {
+            pos: Vec2::default(),
+            vel: Vec2::default(),
+            age: 0.0,
+            lifetime: 0.0,
+        };
+
+        Pool {
+            particles: vec![dead; capacity],
+            alive: vec![false; capacity],
+            free: (0..capacity).rev().collect(),
+        }
+    }
+
+    /// Adds a particle, unless the pool is full, in which case the particle
+    /// is silently dropped.
+    fn add(&mut self, particle: Particle) {
+        if let Some(slot) = self.free.pop() {
+            self.particles[slot] = particle;
+            self.alive[slot] = true;
+        }
+    }
+
+    fn kill(&mut self, slot: usize) {
+        self.alive[slot] = false;
+        self.free.push(slot);
+    }
+}
+
+/// Spawns particles and keeps them moving.
+pub struct Emitter {
+    config: ParticleConfig,
+    emission: Emission,
+    pool: Pool,
+    // Drawn as plain rectangles when missing.
+    sprite: Option<Sprite>,
+    position: Vec2,
+    active: bool,
+    // The fraction of a particle left to emit by continuous emitters.
+    pending: f64,
+    rng_state: u32,
+}
+
+impl Emitter {
+    pub fn new(config: ParticleConfig, emission: Emission, capacity: usize) -> Emitter {
+        Emitter {
+            config: config,
+            emission: emission,
+            pool: Pool::with_capacity(capacity),
+            sprite: None,
+            position: Vec2::default(),
+            active: true,
+            pending: 0.0,
+            rng_state: ::rand::random::<u32>() | 1,
+        }
+    }
+
+    /// Draws the particles with the given sprite, tinted by their color.
+    pub fn with_sprite(mut self, sprite: Sprite) -> Emitter {
+        self.sprite = Some(sprite);
+        self
+    }
+
+    /// Moves the emitter, for instance to follow what it is attached to.
+    pub fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+
+    /// Starts or stops continuous emission. Particles already emitted live
+    /// on.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    /// Emits `count` particles at once from `at`.
+    pub fn burst(&mut self, at: Vec2, count: usize) {
+        for _ in 0..count {
+            self.emit(at);
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if let Emission::Continuous(rate) = self.emission {
+            if self.active {
+                self.pending += rate * dt;
+
+                let position = self.position;
+                while self.pending >= 1.0 {
+                    self.pending -= 1.0;
+                    self.emit(position);
+                }
+            }
+        }
+
+        let drag = (1.0 - self.config.drag * dt).max(0.0);
+
+        for slot in 0..self.pool.particles.len() {
+            if !self.pool.alive[slot] {
+                continue;
+            }
+
+            let particle = &mut self.pool.particles[slot];
+            particle.age += dt;
+
+            if particle.age >= particle.lifetime {
+                self.pool.kill(slot);
+                continue;
+            }
+
+            particle.vel = (particle.vel + self.config.gravity * dt) * drag;
+            particle.pos += particle.vel * dt;
+        }
+    }
+
+    pub fn render(&self, phi: &mut Phi) {
+        phi.renderer.set_blend_mode(BlendMode::Blend);
+
+        for (particle, &alive) in self.pool.particles.iter().zip(&self.pool.alive) {
+            if !alive {
+                continue;
+            }
+
+            let t = particle.age / particle.lifetime;
+            let size = lerp(self.config.sizes.0, self.config.sizes.1, t);
+            let color = lerp_color(self.config.colors.0, self.config.colors.1, t);
+            let rect = Rectangle::from_center(particle.pos, size, size);
+
+            match self.sprite {
+                Some(ref sprite) => sprite.render_tinted(&mut phi.renderer, rect, color),
+                None => {
+                    phi.renderer.set_draw_color(color);
+                    let _ = phi.renderer.fill_rect(rect.to_sdl());
+                }
+            }
+        }
+
+        phi.renderer.set_blend_mode(BlendMode::None);
+    }
+
+    fn emit(&mut self, at: Vec2) {
+        let config = self.config;
+        let angle = config.direction + (self.random() * 2.0 - 1.0) * config.spread;
+        let speed = lerp(config.speed.0, config.speed.1, self.random());
+        let lifetime = lerp(config.lifetime.0, config.lifetime.1, self.random());
+
+        self.pool.add(Particle {
+            pos: at,
+            vel: Vec2::new(angle.cos(), angle.sin()) * speed,
+            age: 0.0,
+            lifetime: lifetime.max(::std::f64::EPSILON),
+        });
+    }
+
+    /// A cheap xorshift generator, between 0 and 1.
+    fn random(&mut self) -> f64 {
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 17;
+        self.rng_state ^= self.rng_state << 5;
+        self.rng_state as f64 / ::std::u32::MAX as f64
+    }
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+fn lerp_color(from: Color, to: Color, t: f64) -> Color {
+    let (r1, g1, b1, a1) = from.rgba();
+    let (r2, g2, b2, a2) = to.rgba();
+    let channel = |a: u8, b: u8| lerp(a as f64, b as f64, t).round() as u8;
+
+    Color::RGBA(channel(r1, r2), channel(g1, g2), channel(b1, b2), channel(a1, a2))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: ParticleConfig = ParticleConfig {
+        lifetime: (1.0, 2.0),
+        speed: (10.0, 20.0),
+        direction: 0.0,
+        spread: ALL_AROUND,
+        gravity: Vec2 { x: 0.0, y: 0.0 },
+        drag: 0.0,
+        colors: (Color::RGBA(255, 0, 0, 255), Color::RGBA(0, 0, 255, 0)),
+        sizes: (4.0, 1.0),
+    };
+
+    fn particle(lifetime: f64) -> Particle {
+        Particle {
+            pos: Vec2::default(),
+            vel: Vec2::default(),
+            age: 0.0,
+            lifetime: lifetime,
+        }
+    }
+
+    fn alive(emitter: &Emitter) -> usize {
+        emitter.pool.alive.iter().filter(|&&alive| alive).count()
+    }
+
+    #[test]
+    fn pools_reuse_the_slots_of_dead_particles() {
+        let mut pool = Pool::with_capacity(3);
+        for _ in 0..3 {
+            pool.add(particle(1.0));
+        }
+
+        pool.kill(1);
+        assert_eq!(pool.alive, vec![true, false, true]);
+
+        pool.add(particle(5.0));
+        assert_eq!(pool.alive, vec![true, true, true]);
+        assert_eq!(pool.particles[1].lifetime, 5.0);
+        assert_eq!(pool.particles.len(), 3);
+    }
+
+    #[test]
+    fn full_pools_drop_new_particles() {
+        let mut pool = Pool::with_capacity(2);
+        pool.add(particle(1.0));
+        pool.add(particle(2.0));
+        pool.add(particle(3.0));
+
+        assert_eq!(pool.alive, vec![true, true]);
+        assert_eq!(pool.particles[0].lifetime, 1.0);
+        assert_eq!(pool.particles[1].lifetime, 2.0);
+
+        let mut emitter = Emitter::new(CONFIG, Emission::Burst, 8);
+        emitter.burst(Vec2::default(), 100);
+        assert_eq!(alive(&emitter), 8);
+    }
+
+    #[test]
+    fn particles_die_at_the_end_of_their_lifetime() {
+        let mut emitter = Emitter::new(CONFIG, Emission::Burst, 64);
+        emitter.burst(Vec2::new(100.0, 100.0), 64);
+
+        emitter.update(0.99);
+        assert_eq!(alive(&emitter), 64);
+
+        // Lifetimes are spread between one and two seconds.
+        emitter.update(0.5);
+        let halfway = alive(&emitter);
+        assert!(halfway < 64);
+
+        emitter.update(0.52);
+        assert_eq!(alive(&emitter), 0);
+
+        // Their slots can then be used again.
+        emitter.burst(Vec2::default(), 64);
+        assert_eq!(alive(&emitter), 64);
+    }
+
+    #[test]
+    fn continuous_emitters_keep_the_fractions_of_particles() {
+        let mut emitter = Emitter::new(CONFIG, Emission::Continuous(10.0), 64);
+
+        for _ in 0..10 {
+            emitter.update(0.05);
+        }
+        assert_eq!(alive(&emitter), 5);
+
+        emitter.set_active(false);
+        emitter.update(0.5);
+        assert_eq!(alive(&emitter), 5);
+    }
+
+    #[test]
+    fn colors_fade_over_the_lifetime() {
+        let (from, to) = CONFIG.colors;
+
+        assert_eq!(lerp_color(from, to, 0.0), from);
+        assert_eq!(lerp_color(from, to, 1.0), to);
+        assert_eq!(lerp_color(from, to, 0.5), Color::RGBA(128, 0, 128, 128));
+    }
+}
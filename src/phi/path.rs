@@ -0,0 +1,190 @@
+/// A piece of a path. Offsets are relative to where the previous segment
+/// ended, so that paths can be chained and reused from any starting point.
+/// Segments lasting no time jump straight to their end.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Segment {
+    /// Moves in a straight line by the given offset.
+    Line { by: (f64, f64), duration: f64 },
+    /// Moves by the given offset along a quadratic Bézier curve, bending
+    /// towards `control`.
+    Curve { control: (f64, f64), by: (f64, f64), duration: f64 },
+    /// Stays in place.
+    Wait { duration: f64 },
+}
+
+impl Segment {
+    pub fn duration(&self) -> f64 {
+        match *self {
+            Segment::Line { duration, .. } |
+            Segment::Curve { duration, .. } |
+            Segment::Wait { duration } => duration,
+        }
+    }
+
+    /// The offset by which the segment moves once it is complete.
+    pub fn end(&self) -> (f64, f64) {
+        match *self {
+            Segment::Line { by, .. } | Segment::Curve { by, .. } => by,
+            Segment::Wait { .. } => (0.0, 0.0),
+        }
+    }
+
+    /// The offset reached after a fraction `t`, between 0 and 1, of the
+    /// segment.
+    fn offset_at(&self, t: f64) -> (f64, f64) {
+        match *self {
+            Segment::Line { by, .. } => (by.0 * t, by.1 * t),
+            Segment::Curve { control, by, .. } => {
+                let u = 1.0 - t;
+                (2.0 * u * t * control.0 + t * t * by.0,
+                 2.0 * u * t * control.1 + t * t * by.1)
+            }
+            Segment::Wait { .. } => (0.0, 0.0),
+        }
+    }
+}
+
+/// A sequence of segments describing how something moves over time, built by
+/// chaining calls such as `Path::new().line(-100.0, 0.0, 1.0).wait(0.5)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    pub fn new() -> Path {
+        Path { segments: vec![] }
+    }
+
+    pub fn line(mut self, dx: f64, dy: f64, duration: f64) -> Path {
+        self.segments.push(Segment::Line {
+            by: (dx, dy),
+            duration: duration,
+        });
+        self
+    }
+
+    pub fn curve(mut self, control: (f64, f64), dx: f64, dy: f64, duration: f64) -> Path {
+        self.segments.push(Segment::Curve {
+            control: control,
+            by: (dx, dy),
+            duration: duration,
+        });
+        self
+    }
+
+    pub fn wait(mut self, duration: f64) -> Path {
+        self.segments.push(Segment::Wait { duration: duration });
+        self
+    }
+
+    /// Appends another path, which starts where this one ends.
+    pub fn then(mut self, other: Path) -> Path {
+        self.segments.extend(other.segments);
+        self
+    }
+
+    pub fn repeat(self, times: usize) -> Path {
+        let mut segments = Vec::with_capacity(self.segments.len() * times);
+        for _ in 0..times {
+            segments.extend_from_slice(&self.segments);
+        }
+
+        Path { segments: segments }
+    }
+
+    /// The offset from the start of the path after `time` seconds, or `None`
+    /// once the path is complete.
+    pub fn offset_at(&self, time: f64) -> Option<(f64, f64)> {
+        let mut start = (0.0, 0.0);
+        let mut remaining = time.max(0.0);
+
+        for segment in &self.segments {
+            let duration = segment.duration();
+
+            if remaining < duration {
+                let (dx, dy) = segment.offset_at(remaining / duration);
+                return Some((start.0 + dx, start.1 + dy));
+            }
+
+            remaining -= duration;
+            let (dx, dy) = segment.end();
+            start = (start.0 + dx, start.1 + dy);
+        }
+
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_at(path: &Path, time: f64, expected: (f64, f64)) {
+        let (x, y) = path.offset_at(time).expect("the path ended too early");
+        assert!((x - expected.0).abs() < 1e-9 && (y - expected.1).abs() < 1e-9,
+                "expected {:?} after {}s, got {:?}", expected, time, (x, y));
+    }
+
+    #[test]
+    fn segments_start_where_the_previous_ones_ended() {
+        let path = Path::new().line(100.0, 0.0, 2.0).wait(1.0).line(0.0, -50.0, 1.0);
+
+        assert_at(&path, -1.0, (0.0, 0.0));
+        assert_at(&path, 0.0, (0.0, 0.0));
+        assert_at(&path, 1.0, (50.0, 0.0));
+        assert_at(&path, 2.0, (100.0, 0.0));
+        assert_at(&path, 2.5, (100.0, 0.0));
+        assert_at(&path, 3.0, (100.0, 0.0));
+        assert_at(&path, 3.5, (100.0, -25.0));
+    }
+
+    #[test]
+    fn ends_once_every_segment_is_done() {
+        let path = Path::new().line(10.0, 10.0, 1.0);
+
+        assert_at(&path, 0.999, (9.99, 9.99));
+        assert_eq!(path.offset_at(1.0), None);
+        assert_eq!(path.offset_at(5.0), None);
+        assert_eq!(Path::new().offset_at(0.0), None);
+    }
+
+    #[test]
+    fn curves_bend_towards_their_control_point() {
+        let path = Path::new().curve((0.0, 100.0), 100.0, 0.0, 1.0);
+
+        assert_at(&path, 0.5, (25.0, 50.0));
+        assert_at(&path, 0.25, (6.25, 37.5));
+        assert_eq!(Segment::Curve { control: (0.0, 100.0), by: (100.0, 0.0), duration: 1.0 }.end(),
+                   (100.0, 0.0));
+    }
+
+    #[test]
+    fn chains_and_repeats_paths() {
+        let step = Path::new().line(10.0, 0.0, 1.0).line(0.0, 10.0, 1.0);
+        let stairs = step.clone().repeat(3);
+
+        assert_at(&stairs, 2.5, (15.0, 10.0));
+        assert_at(&stairs, 5.5, (30.0, 25.0));
+        assert_eq!(stairs.offset_at(6.0), None);
+        assert_eq!(step.clone().repeat(0).offset_at(0.0), None);
+
+        let path = step.clone().then(Path::new().wait(1.0)).then(step);
+        assert_at(&path, 2.5, (10.0, 10.0));
+        assert_at(&path, 3.5, (15.0, 10.0));
+        assert_eq!(path.offset_at(5.0), None);
+    }
+
+    #[test]
+    fn zero_duration_segments_jump_to_their_end() {
+        let path = Path::new().line(10.0, 0.0, 1.0).line(0.0, 50.0, 0.0).line(10.0, 0.0, 1.0);
+
+        assert_at(&path, 0.5, (5.0, 0.0));
+        assert_at(&path, 1.0, (10.0, 50.0));
+        assert_at(&path, 1.5, (15.0, 50.0));
+
+        // A path made of jumps alone is over at once.
+        assert_eq!(Path::new().line(10.0, 0.0, 0.0).offset_at(0.0), None);
+    }
+}
@@ -0,0 +1,381 @@
+//! Smoothly animates values over time.
+//!
+//! A `Tween` goes from one value to another in a given time, following an
+//! easing curve, while a `Sequence` chains tweens, pauses and callbacks:
+//!
+//! ```ignore
+//! let mut slide = Sequence::new(-200.0)
+//!     .wait(0.2)
+//!     .to(40.0, 0.5, Easing::BounceOut)
+//!     .call(|| println!("Arrived"));
+//!
+//! slide.update(dt);
+//! let x = slide.value();
+//! ```
+
+use phi::data::Rectangle;
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+/// How the progress of a tween is spread over its duration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    /// Overshoots the target, then springs back and forth around it.
+    ElasticOut,
+    /// Reaches the target, then bounces off it a few times.
+    BounceOut,
+}
+
+impl Easing {
+    /// Maps the fraction `t` of the time elapsed, between 0 and 1, to the
+    /// fraction of the distance covered. Elastic easing goes beyond 1 for a
+    /// while.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.max(0.0).min(1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - 4.0 * (1.0 - t).powi(3)
+                }
+            }
+            Easing::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2f64.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+            Easing::BounceOut => {
+                const N: f64 = 7.5625;
+                const D: f64 = 2.75;
+
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+/// Values which can be animated.
+pub trait Lerp: Copy {
+    /// The value found at a fraction `t` of the way to `to`.
+    fn lerp(self, to: Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(self, to: f64, t: f64) -> f64 {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Rectangle {
+    fn lerp(self, to: Rectangle, t: f64) -> Rectangle {
+        Rectangle {
+            x: self.x.lerp(to.x, t),
+            y: self.y.lerp(to.y, t),
+            w: self.w.lerp(to.w, t),
+            h: self.h.lerp(to.h, t),
+        }
+    }
+}
+
+/// Animates a value from `from` to `to` in `duration` seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween<T: Lerp> {
+    from: T,
+    to: T,
+    duration: f64,
+    elapsed: f64,
+    easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    pub fn new(from: T, to: T, duration: f64, easing: Easing) -> Tween<T> {
+        Tween {
+            from: from,
+            to: to,
+            duration: duration,
+            elapsed: 0.0,
+            easing: easing,
+        }
+    }
+
+    /// A tween which already reached `value`, to be retargeted later.
+    pub fn at(value: T) -> Tween<T> {
+        Tween::new(value, value, 0.0, Easing::Linear)
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub fn value(&self) -> T {
+        if self.duration <= 0.0 {
+            self.to
+        } else {
+            self.from.lerp(self.to, self.easing.apply(self.elapsed / self.duration))
+        }
+    }
+
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Heads towards a new value, starting from wherever the tween currently
+    /// is so that interrupting it does not make the value jump.
+    pub fn retarget(&mut self, to: T, duration: f64, easing: Easing) {
+        *self = Tween::new(self.value(), to, duration, easing);
+    }
+
+    /// The time left over once the tween is done, when it was updated by more
+    /// than it needed.
+    fn overflow(&self, dt: f64) -> f64 {
+        (self.elapsed + dt - self.duration).max(0.0)
+    }
+}
+
+enum Step<T: Lerp> {
+    To(T, f64, Easing),
+    Wait(f64),
+    Call(Box<dyn FnMut()>),
+}
+
+/// Runs tweens, pauses and callbacks one after the other.
+pub struct Sequence<T: Lerp> {
+    current: Tween<T>,
+    steps: VecDeque<Step<T>>,
+}
+
+impl<T: Lerp> Sequence<T> {
+    /// An empty sequence which stays at `start` until steps are added.
+    pub fn new(start: T) -> Sequence<T> {
+        Sequence {
+            current: Tween::at(start),
+            steps: VecDeque::new(),
+        }
+    }
+
+    /// Tweens from wherever the previous step left the value to `to`.
+    pub fn to(mut self, to: T, duration: f64, easing: Easing) -> Sequence<T> {
+        self.steps.push_back(Step::To(to, duration, easing));
+        self
+    }
+
+    /// Keeps the value where it is for `duration` seconds.
+    pub fn wait(mut self, duration: f64) -> Sequence<T> {
+        self.steps.push_back(Step::Wait(duration));
+        self
+    }
+
+    /// Calls `callback` once the previous steps are done.
+    pub fn call<F: FnMut() + 'static>(mut self, callback: F) -> Sequence<T> {
+        self.steps.push_back(Step::Call(Box::new(callback)));
+        self
+    }
+
+    /// Advances the sequence, possibly going through several steps, and
+    /// running their callbacks, if `dt` is long enough.
+    pub fn update(&mut self, dt: f64) {
+        let mut dt = dt;
+
+        loop {
+            let left = self.current.overflow(dt);
+            self.current.update(dt);
+
+            if !self.current.is_done() {
+                return;
+            }
+
+            let value = self.current.target();
+
+            match self.steps.pop_front() {
+                Some(Step::To(to, duration, easing)) => {
+                    self.current = Tween::new(value, to, duration, easing);
+                }
+                Some(Step::Wait(duration)) => {
+                    self.current = Tween::new(value, value, duration, Easing::Linear);
+                }
+                Some(Step::Call(mut callback)) => {
+                    callback();
+                    self.current = Tween::at(value);
+                }
+                None => return,
+            }
+
+            dt = left;
+        }
+    }
+
+    pub fn value(&self) -> T {
+        self.current.value()
+    }
+
+    /// Whether every step was run.
+    pub fn is_done(&self) -> bool {
+        self.current.is_done() && self.steps.is_empty()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    const EASINGS: [Easing; 9] = [Easing::Linear, Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+                                  Easing::CubicIn, Easing::CubicOut, Easing::CubicInOut,
+                                  Easing::ElasticOut, Easing::BounceOut];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for &easing in &EASINGS {
+            assert_close(easing.apply(0.0), 0.0);
+            assert_close(easing.apply(1.0), 1.0);
+
+            // Time outside of the tween is clamped.
+            assert_close(easing.apply(-1.0), 0.0);
+            assert_close(easing.apply(2.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn polynomial_easings_reach_their_midpoint() {
+        assert_close(Easing::Linear.apply(0.5), 0.5);
+        assert_close(Easing::QuadIn.apply(0.5), 0.25);
+        assert_close(Easing::QuadOut.apply(0.5), 0.75);
+        assert_close(Easing::QuadInOut.apply(0.5), 0.5);
+        assert_close(Easing::CubicIn.apply(0.5), 0.125);
+        assert_close(Easing::CubicOut.apply(0.5), 0.875);
+        assert_close(Easing::CubicInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn in_out_easings_are_symmetric() {
+        for &(easing, ease_in) in &[(Easing::QuadInOut, Easing::QuadIn), (Easing::CubicInOut, Easing::CubicIn)] {
+            // The first half is the in easing, squeezed into half the time.
+            assert_close(easing.apply(0.25), ease_in.apply(0.5) / 2.0);
+
+            for i in 0..11 {
+                let t = i as f64 / 10.0;
+                assert_close(easing.apply(t), 1.0 - easing.apply(1.0 - t));
+            }
+        }
+    }
+
+    #[test]
+    fn rectangles_are_lerped_side_by_side() {
+        let from = Rectangle { x: 0.0, y: 10.0, w: 20.0, h: 40.0 };
+        let to = Rectangle { x: 100.0, y: -10.0, w: 40.0, h: 40.0 };
+
+        assert_eq!(from.lerp(to, 0.0), from);
+        assert_eq!(from.lerp(to, 1.0), to);
+        assert_eq!(from.lerp(to, 0.5), Rectangle { x: 50.0, y: 0.0, w: 30.0, h: 40.0 });
+
+        let mut tween = Tween::new(from, to, 2.0, Easing::QuadIn);
+        tween.update(1.0);
+        assert_eq!(tween.value(), Rectangle { x: 25.0, y: 5.0, w: 25.0, h: 40.0 });
+    }
+
+    #[test]
+    fn elastic_easing_overshoots() {
+        assert!((1..100).any(|i| Easing::ElasticOut.apply(i as f64 / 100.0) > 1.0));
+        assert!((1..100).all(|i| Easing::QuadOut.apply(i as f64 / 100.0) < 1.0));
+    }
+
+    #[test]
+    fn tweens_retarget_from_where_they_are() {
+        let mut tween = Tween::new(0.0, 10.0, 2.0, Easing::Linear);
+        tween.update(1.0);
+        assert_close(tween.value(), 5.0);
+        assert!(!tween.is_done());
+
+        tween.retarget(0.0, 1.0, Easing::Linear);
+        assert_close(tween.value(), 5.0);
+        tween.update(5.0);
+        assert_close(tween.value(), 0.0);
+        assert!(tween.is_done());
+    }
+
+    #[test]
+    fn sequences_carry_the_time_left_over_to_the_next_steps() {
+        let mut sequence = Sequence::new(0.0)
+            .to(10.0, 1.0, Easing::Linear)
+            .wait(0.5)
+            .to(20.0, 1.0, Easing::Linear);
+
+        // Through the first tween and the pause, a quarter into the second.
+        sequence.update(1.75);
+        assert_close(sequence.value(), 12.5);
+        assert!(!sequence.is_done());
+
+        sequence.update(10.0);
+        assert_close(sequence.value(), 20.0);
+        assert!(sequence.is_done());
+    }
+
+    #[test]
+    fn sequences_call_back_once_reached() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+
+        let mut sequence = Sequence::new(0.0)
+            .to(1.0, 1.0, Easing::Linear)
+            .call(move || counter.set(counter.get() + 1))
+            .to(0.0, 1.0, Easing::Linear);
+
+        assert!(!sequence.is_done());
+        sequence.update(0.9);
+        assert_eq!(calls.get(), 0);
+
+        sequence.update(0.2);
+        assert_eq!(calls.get(), 1);
+        assert_close(sequence.value(), 0.9);
+
+        sequence.update(5.0);
+        assert_eq!(calls.get(), 1);
+        assert_close(sequence.value(), 0.0);
+        assert!(sequence.is_done());
+        assert!(Sequence::new(0.0).is_done());
+    }
+}
@@ -0,0 +1,472 @@
+use phi::Phi;
+use phi::data::{Rectangle, Vec2};
+use phi::ecs::{Body, Collider, Drawable, Entity, Offscreen, Shape, Velocity, World};
+use phi::gfx::{AnimatedSprite, Sprite};
+use rand::Rng;
+use views::weapons::Health;
+
+const ASTEROID_PATH: &'static str = "assets/asteroid.png";
+const ASTEROIDS_WIDE: usize = 21;
+const ASTEROIDS_HIGH: usize = 7;
+const ASTEROIDS_TOTAL: usize = ASTEROIDS_WIDE * ASTEROIDS_HIGH - 4;
+const ASTEROID_SIDE: f64 = 96.0;
+/// The radius of the area which can be hit, relative to the side of the
+/// sprite, which has some empty space around the rock.
+const HIT_RADIUS: f64 = 0.42;
+/// How many fragments a destroyed asteroid splits into.
+const FRAGMENTS: usize = 2;
+/// The maximum speed, in pixels per second, added in a random direction to the
+/// velocity fragments inherit from their parent.
+const FRAGMENT_SPREAD: f64 = 90.0;
+/// The collision layer of asteroids.
+pub const ASTEROID_LAYER: u32 = 2;
+
+/// The size classes of asteroids. Every class reuses the same sprite sheet,
+/// scaled down for the smaller ones.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AsteroidSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl AsteroidSize {
+    pub fn side(self) -> f64 {
+        match self {
+            AsteroidSize::Small => ASTEROID_SIDE * 0.4,
+            AsteroidSize::Medium => ASTEROID_SIDE * 0.65,
+            AsteroidSize::Large => ASTEROID_SIDE,
+        }
+    }
+
+    pub fn hp(self) -> u32 {
+        match self {
+            AsteroidSize::Small => 1,
+            AsteroidSize::Medium => 2,
+            AsteroidSize::Large => 4,
+        }
+    }
+
+    /// The points awarded for shooting down an asteroid of this size. Smaller
+    /// asteroids are harder to hit, hence worth more.
+    pub fn score(self) -> u32 {
+        match self {
+            AsteroidSize::Small => 150,
+            AsteroidSize::Medium => 100,
+            AsteroidSize::Large => 50,
+        }
+    }
+
+    /// The size of the fragments this asteroid splits into when destroyed.
+    pub fn fragment(self) -> Option<AsteroidSize> {
+        match self {
+            AsteroidSize::Small => None,
+            AsteroidSize::Medium => Some(AsteroidSize::Small),
+            AsteroidSize::Large => Some(AsteroidSize::Medium),
+        }
+    }
+
+    /// Smaller asteroids drift faster than the large ones.
+    fn speed_range(self) -> (f64, f64) {
+        match self {
+            AsteroidSize::Small => (120.0, 220.0),
+            AsteroidSize::Medium => (80.0, 170.0),
+            AsteroidSize::Large => (50.0, 150.0),
+        }
+    }
+}
+
+/// Makes an entity an asteroid of the given size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Asteroid {
+    pub size: AsteroidSize,
+}
+
+/// Loads the asteroid sprite sheet once and hands out asteroids sharing it.
+pub struct AsteroidFactory {
+    sprite: AnimatedSprite,
+}
+
+impl AsteroidFactory {
+    pub fn new(phi: &mut Phi) -> AsteroidFactory {
+        let asteroid_spritesheet = Sprite::load(&mut phi.renderer, ASTEROID_PATH).unwrap();
+        let mut asteroid_sprites = Vec::with_capacity(ASTEROIDS_TOTAL);
+
+        for yth in 0..ASTEROIDS_HIGH {
+            for xth in 0..ASTEROIDS_WIDE {
+                if ASTEROIDS_WIDE * yth + xth >= ASTEROIDS_TOTAL {
+                    break;
+                }
+
+                asteroid_sprites.push(
+                    asteroid_spritesheet.region(Rectangle {
+                        w: ASTEROID_SIDE,
+                        h: ASTEROID_SIDE,
+                        x: ASTEROID_SIDE * xth as f64,
+                        y: ASTEROID_SIDE * yth as f64,
+                    }).unwrap());
+            }
+        }
+
+        AsteroidFactory {
+            sprite: AnimatedSprite::with_fps(asteroid_sprites, 1.0),
+        }
+    }
+
+    /// Spawns an asteroid of the given size just off the right edge of the
+    /// screen, at a random height and speed.
+    pub fn random<R: Rng>(&self, phi: &mut Phi, world: &mut World, rng: &mut R, size: AsteroidSize) -> Entity {
+        let at = rng.gen::<f64>();
+        self.at(phi, world, rng, size, at)
+    }
+
+    /// Spawns an asteroid of the given size just off the right edge of the
+    /// screen, `at` being a fraction of the height of the screen.
+    pub fn at<R: Rng>(&self, phi: &mut Phi, world: &mut World, rng: &mut R, size: AsteroidSize, at: f64) -> Entity {
+        let (w, h) = phi.output_size();
+        let side = size.side();
+        let (min_vel, max_vel) = size.speed_range();
+
+        let rect = Rectangle {
+            w: side,
+            h: side,
+            x: w,
+            y: at * (h - side),
+        };
+
+        let speed = min_vel + rng.gen::<f64>() * (max_vel - min_vel);
+        self.build(world, rng, size, rect, Vec2::new(-speed, 0.0))
+    }
+
+    /// Splits a destroyed asteroid into smaller ones, which keep moving in
+    /// roughly the same direction as their parent. The parent itself is left
+    /// alone.
+    pub fn fragments<R: Rng>(&self, world: &mut World, rng: &mut R, parent: Entity) -> Vec<Entity> {
+        let size = match world.get::<Asteroid>(parent).and_then(|asteroid| asteroid.size.fragment()) {
+            Some(size) => size,
+            None => return vec![],
+        };
+
+        let center = match world.get::<Body>(parent) {
+            Some(&Body(rect)) => rect.center(),
+            None => return vec![],
+        };
+
+        let parent_vel = world.get::<Velocity>(parent).map_or(Vec2::default(), |&Velocity(vel)| vel);
+        let side = size.side();
+
+        (0..FRAGMENTS).map(|_| {
+            let angle = rng.gen::<f64>() * 2.0 * ::std::f64::consts::PI;
+            let spread = rng.gen::<f64>() * FRAGMENT_SPREAD;
+
+            let rect = Rectangle::from_center(center, side, side);
+            let vel = parent_vel + Vec2::new(angle.cos(), angle.sin()) * spread;
+            self.build(world, rng, size, rect, vel)
+        }).collect()
+    }
+
+    fn build<R: Rng>(&self, world: &mut World, rng: &mut R, size: AsteroidSize, rect: Rectangle, vel: Vec2) -> Entity {
+        let mut sprite = self.sprite.clone();
+        sprite.set_fps(rng.gen::<f64>() * 20.0 + 10.0);
+
+        let asteroid = world.spawn();
+        world.insert(asteroid, Asteroid { size: size });
+        world.insert(asteroid, Body(rect));
+        world.insert(asteroid, Velocity(vel));
+        world.insert(asteroid, Drawable::Animated(sprite));
+        world.insert(asteroid, Health(size.hp()));
+        world.insert(asteroid, Shape::Circle(HIT_RADIUS));
+        world.insert(asteroid, Offscreen);
+        world.insert(asteroid, Collider {
+            layers: ASTEROID_LAYER,
+            collides_with: 0,
+        });
+        asteroid
+    }
+}
+
+/// Describes how often asteroids appear and how that evolves over time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpawnConfig {
+    /// Asteroids spawned per second at the start of the game.
+    pub rate: f64,
+    /// How much the spawn rate increases every second.
+    pub rate_ramp: f64,
+    pub max_rate: f64,
+    /// The probability that a spawn produces a burst of asteroids instead of
+    /// a single one.
+    pub burst_chance: f64,
+    pub burst_size: usize,
+    /// The maximum amount of asteroids alive at the same time.
+    pub max_concurrent: usize,
+    /// The relative likelihood of the small, medium and large size classes.
+    pub size_weights: [f64; 3],
+}
+
+impl Default for SpawnConfig {
+    fn default() -> SpawnConfig {
+        SpawnConfig {
+            rate: 0.5,
+            rate_ramp: 0.01,
+            max_rate: 2.5,
+            burst_chance: 0.1,
+            burst_size: 3,
+            max_concurrent: 12,
+            size_weights: [0.3, 0.3, 0.4],
+        }
+    }
+}
+
+/// Decides when asteroids should appear and what their size should be.
+pub struct AsteroidSpawner {
+    config: SpawnConfig,
+    // How long the spawner has been running, which drives the difficulty.
+    elapsed: f64,
+    // Counted in spawns rather than in seconds, so that the wait shortens as
+    // the rate ramps up, and never ends while the rate is zero.
+    next_spawn: f64,
+}
+
+impl AsteroidSpawner {
+    pub fn new(config: SpawnConfig) -> AsteroidSpawner {
+        AsteroidSpawner {
+            config: config,
+            elapsed: 0.0,
+            // The first asteroid shows up at once, unless the rate starts at
+            // zero, in which case it waits for a whole interval once the rate
+            // ramps up.
+            next_spawn: if config.rate > 0.0 { 0.0 } else { 1.0 },
+        }
+    }
+
+    /// The current amount of spawns per second.
+    pub fn rate(&self) -> f64 {
+        (self.config.rate + self.config.rate_ramp * self.elapsed).min(self.config.max_rate)
+    }
+
+    /// Advances time and returns the sizes of the asteroids which should be
+    /// spawned this frame, given how many of them are currently alive.
+    pub fn update<R: Rng>(&mut self, rng: &mut R, dt: f64, alive: usize) -> Vec<AsteroidSize> {
+        self.elapsed += dt;
+
+        self.next_spawn -= self.rate().max(0.0) * dt;
+
+        let mut spawned = vec![];
+        if self.next_spawn > 0.0 {
+            return spawned;
+        }
+
+        // Spawns are spaced randomly around the average interval so that
+        // asteroids do not arrive in a predictable rhythm.
+        self.next_spawn = 0.5 + rng.gen::<f64>();
+
+        let count = if rng.gen::<f64>() < self.config.burst_chance {
+            self.config.burst_size
+        } else {
+            1
+        };
+
+        for _ in 0..count {
+            if alive + spawned.len() >= self.config.max_concurrent {
+                break;
+            }
+
+            spawned.push(self.random_size(rng));
+        }
+
+        spawned
+    }
+
+    fn random_size<R: Rng>(&self, rng: &mut R) -> AsteroidSize {
+        let [small, medium, large] = self.config.size_weights;
+        let pick = rng.gen::<f64>() * (small + medium + large);
+
+        if pick < small {
+            AsteroidSize::Small
+        } else if pick < small + medium {
+            AsteroidSize::Medium
+        } else {
+            AsteroidSize::Large
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, XorShiftRng};
+
+    const DT: f64 = 0.01;
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    fn steady(rate: f64) -> SpawnConfig {
+        SpawnConfig {
+            rate: rate,
+            rate_ramp: 0.0,
+            max_rate: 100.0,
+            burst_chance: 0.0,
+            burst_size: 1,
+            max_concurrent: 1_000,
+            ..SpawnConfig::default()
+        }
+    }
+
+    /// Runs the spawner for `duration` seconds, never having any asteroid
+    /// alive, and returns when every asteroid was spawned.
+    fn run(spawner: &mut AsteroidSpawner, duration: f64) -> Vec<f64> {
+        let mut rng = rng();
+        let mut spawns = vec![];
+
+        for frame in 0..(duration / DT).round() as usize {
+            for _ in spawner.update(&mut rng, DT, 0) {
+                spawns.push((frame + 1) as f64 * DT);
+            }
+        }
+
+        spawns
+    }
+
+    #[test]
+    fn spawns_at_the_average_rate() {
+        let spawns = run(&mut AsteroidSpawner::new(steady(2.0)), 100.0);
+        assert!(spawns.len() >= 190 && spawns.len() <= 210, "{} spawns", spawns.len());
+
+        // Intervals vary between half and one and a half the average.
+        for pair in spawns[1..].windows(2) {
+            let interval = pair[1] - pair[0];
+            assert!(interval >= 0.25 - DT && interval <= 0.75 + DT, "waited {}s", interval);
+        }
+    }
+
+    #[test]
+    fn ramps_the_rate_up_to_its_maximum() {
+        let mut spawner = AsteroidSpawner::new(SpawnConfig {
+            rate: 0.5,
+            rate_ramp: 0.1,
+            max_rate: 1.0,
+            ..steady(0.0)
+        });
+
+        assert_eq!(spawner.rate(), 0.5);
+        run(&mut spawner, 2.0);
+        assert!((spawner.rate() - 0.7).abs() < 1e-9);
+        run(&mut spawner, 10.0);
+        assert_eq!(spawner.rate(), 1.0);
+
+        // Spawns get more frequent as the rate goes up.
+        let mut spawner = AsteroidSpawner::new(SpawnConfig { rate_ramp: 0.1, max_rate: 10.0, ..steady(1.0) });
+        let spawns = run(&mut spawner, 60.0);
+        let early = spawns.iter().filter(|&&t| t < 10.0).count();
+        let late = spawns.iter().filter(|&&t| t >= 50.0).count();
+        assert!(late > early * 3, "{} spawns early, {} late", early, late);
+    }
+
+    #[test]
+    fn waits_for_a_zero_rate_to_ramp_up() {
+        assert_eq!(run(&mut AsteroidSpawner::new(steady(0.0)), 60.0), vec![]);
+
+        // The rate reaches 0.1 * t after t seconds, so the first spawn is due
+        // once 0.05 * t * t reaches one, after about 4.5 seconds.
+        let mut spawner = AsteroidSpawner::new(SpawnConfig { rate_ramp: 0.1, ..steady(0.0) });
+        let spawns = run(&mut spawner, 60.0);
+
+        assert!(spawns[0] > 4.4 && spawns[0] < 4.6, "first spawn after {}s", spawns[0]);
+        assert!(spawns.len() > 20);
+    }
+
+    #[test]
+    fn bursts_up_to_the_maximum_alive() {
+        let mut rng = rng();
+        let mut spawner = AsteroidSpawner::new(SpawnConfig {
+            burst_chance: 1.0,
+            burst_size: 3,
+            max_concurrent: 4,
+            ..steady(1.0)
+        });
+
+        assert_eq!(spawner.update(&mut rng, DT, 0).len(), 3);
+        assert_eq!(spawner.update(&mut rng, 10.0, 2).len(), 2);
+        assert_eq!(spawner.update(&mut rng, 10.0, 4).len(), 0);
+    }
+
+    #[test]
+    fn picks_sizes_by_weight() {
+        let mut rng = rng();
+        let spawner = AsteroidSpawner::new(SpawnConfig { size_weights: [0.0, 1.0, 3.0], ..steady(1.0) });
+        let sizes: Vec<AsteroidSize> = (0..1_000).map(|_| spawner.random_size(&mut rng)).collect();
+
+        let count = |size| sizes.iter().filter(|&&s| s == size).count();
+        assert_eq!(count(AsteroidSize::Small), 0);
+        assert!(count(AsteroidSize::Medium) > 200 && count(AsteroidSize::Medium) < 300);
+    }
+
+    fn factory() -> AsteroidFactory {
+        AsteroidFactory { sprite: AnimatedSprite::with_fps(vec![], 1.0) }
+    }
+
+    /// Spawns an asteroid of the given size centered on (200, 100), drifting
+    /// to the left.
+    fn parent(world: &mut World, size: AsteroidSize) -> Entity {
+        let rect = Rectangle::from_center(Vec2::new(200.0, 100.0), size.side(), size.side());
+        factory().build(world, &mut rng(), size, rect, Vec2::new(-100.0, 0.0))
+    }
+
+    #[test]
+    fn splits_into_fragments_of_the_next_size() {
+        let mut rng = rng();
+
+        for &(size, fragment) in &[(AsteroidSize::Large, AsteroidSize::Medium),
+                                   (AsteroidSize::Medium, AsteroidSize::Small)] {
+            let mut world = World::new();
+            let parent = parent(&mut world, size);
+            let fragments = factory().fragments(&mut world, &mut rng, parent);
+
+            assert_eq!(fragments.len(), FRAGMENTS);
+            assert!(world.is_alive(parent));
+
+            for &entity in &fragments {
+                assert_eq!(world.get::<Asteroid>(entity), Some(&Asteroid { size: fragment }));
+                assert_eq!(world.get::<Health>(entity), Some(&Health(fragment.hp())));
+
+                let Body(rect) = *world.get::<Body>(entity).unwrap();
+                assert_eq!((rect.w, rect.h), (fragment.side(), fragment.side()));
+                assert_eq!(rect.center(), Vec2::new(200.0, 100.0));
+            }
+        }
+    }
+
+    #[test]
+    fn the_smallest_asteroids_do_not_split() {
+        let mut world = World::new();
+        let parent = parent(&mut world, AsteroidSize::Small);
+
+        assert_eq!(factory().fragments(&mut world, &mut rng(), parent), vec![]);
+        assert_eq!(world.with::<Asteroid>(), vec![parent]);
+    }
+
+    #[test]
+    fn fragments_spread_around_the_velocity_of_their_parent() {
+        let mut rng = rng();
+        let mut world = World::new();
+        let parent = parent(&mut world, AsteroidSize::Large);
+
+        let fragments: Vec<Entity> = (0..100)
+            .flat_map(|_| factory().fragments(&mut world, &mut rng, parent))
+            .collect();
+
+        let deviations: Vec<f64> = fragments.into_iter()
+            .map(|entity| {
+                let Velocity(vel) = *world.get::<Velocity>(entity).unwrap();
+                (vel - Vec2::new(-100.0, 0.0)).length()
+            })
+            .collect();
+
+        assert!(deviations.iter().all(|&deviation| deviation <= FRAGMENT_SPREAD));
+        assert!(deviations.iter().any(|&deviation| deviation > FRAGMENT_SPREAD * 0.9));
+        assert!(deviations.iter().any(|&deviation| deviation < FRAGMENT_SPREAD * 0.1));
+    }
+}
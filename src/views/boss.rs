@@ -0,0 +1,245 @@
+use phi::Phi;
+use phi::data::{Rectangle, Vec2};
+use phi::ecs::{Body, Collider, Drawable, Entity, Shape, World, ZOrder};
+use phi::gfx::Sprite;
+use phi::path::Path;
+use views::patterns::{self, Aim, Emitter};
+use views::weapons::Health;
+
+const BOSS_PATH: &'static str = "assets/boss.png";
+const BOSS_W: f64 = 192.0;
+const BOSS_H: f64 = 160.0;
+const BOSS_HP: u32 = 120;
+pub const BOSS_SCORE: u32 = 5_000;
+/// The collision layer of the boss.
+pub const BOSS_LAYER: u32 = 8;
+
+/// How far from the right edge of the screen the boss stops after its intro.
+const BOSS_MARGIN: f64 = 24.0;
+const INTRO_DURATION: f64 = 3.0;
+const OUTRO_DURATION: f64 = 2.5;
+/// The time between two explosions while the boss is being destroyed.
+const OUTRO_BLAST_INTERVAL: f64 = 0.15;
+/// The amplitude, in pixels, and the period, in seconds, of the vertical
+/// hovering of the boss.
+const HOVER_AMPLITUDE: f64 = 120.0;
+const HOVER_PERIOD: f64 = 6.0;
+
+/// The parts of the boss which can be hit, relative to its top-left corner:
+/// the hull and both wings.
+const HITBOXES: [Rectangle; 3] = [
+    Rectangle { x: 20.0, y: 30.0, w: 160.0, h: 100.0 },
+    Rectangle { x: 60.0, y: 0.0, w: 90.0, h: 40.0 },
+    Rectangle { x: 60.0, y: 120.0, w: 90.0, h: 40.0 },
+];
+
+/// Where bullets come out of, relative to the top-left corner of the boss.
+const CANNONS: [(f64, f64); 3] = [(0.0, 80.0), (40.0, 26.0), (40.0, 134.0)];
+
+/// A phase of the fight starts once the health of the boss drops to
+/// `threshold`, a fraction of its maximal health, and replaces the patterns
+/// fired from each cannon.
+struct Phase {
+    threshold: f64,
+    patterns: &'static [(&'static str, usize)],
+}
+
+const PHASES: [Phase; 3] = [
+    Phase { threshold: 1.0, patterns: &[("aimed", 0), ("spread", 1), ("spread", 2)] },
+    Phase { threshold: 0.66, patterns: &[("ring", 0), ("aimed", 1), ("aimed", 2)] },
+    Phase { threshold: 0.33, patterns: &[("spiral", 0), ("spread", 1), ("spread", 2)] },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    /// Flies onto the screen, cannot be hurt nor shoots.
+    Intro,
+    Fight,
+    /// Blows up over a few seconds.
+    Outro,
+}
+
+/// Where explosions should be shown over the boss, as it blows up, since the
+/// last run of `update`.
+pub struct Blasts(pub Vec<Rectangle>);
+
+/// Makes an entity the boss, which flies in, fights in phases as its health
+/// goes down, then blows up once it has none left. It only has health while
+/// fighting.
+pub struct Boss {
+    state: State,
+    // The time spent in the current state.
+    time: f64,
+    intro: Path,
+    intro_from: (f64, f64),
+    phase: usize,
+    emitters: Vec<(Emitter, usize)>,
+    next_blast: f64,
+}
+
+impl Boss {
+    /// Spawns the boss just off the right edge of the screen.
+    pub fn spawn(phi: &mut Phi, world: &mut World) -> Entity {
+        let (w, h) = phi.output_size();
+        let intro_from = (w, (h - BOSS_H) / 2.0);
+
+        let mut state = Boss {
+            state: State::Intro,
+            time: 0.0,
+            intro: Path::new().curve((-BOSS_W, -60.0), -(BOSS_W + BOSS_MARGIN), 0.0, INTRO_DURATION),
+            intro_from: intro_from,
+            phase: 0,
+            emitters: vec![],
+            next_blast: 0.0,
+        };
+        state.enter_phase(0);
+
+        let boss = world.spawn();
+        world.insert(boss, Body(Rectangle {
+            x: intro_from.0,
+            y: intro_from.1,
+            w: BOSS_W,
+            h: BOSS_H,
+        }));
+        world.insert(boss, Drawable::Sprite(Sprite::load(&mut phi.renderer, BOSS_PATH).unwrap()));
+        world.insert(boss, ZOrder(-1));
+        world.insert(boss, Shape::Boxes(HITBOXES.to_vec()));
+        world.insert(boss, Collider {
+            layers: BOSS_LAYER,
+            collides_with: 0,
+        });
+        world.insert(boss, state);
+        boss
+    }
+
+    /// Whether the boss has been defeated, even if it is still exploding.
+    pub fn is_defeated(&self) -> bool {
+        self.state == State::Outro
+    }
+
+    fn enter_phase(&mut self, phase: usize) {
+        self.phase = phase;
+        self.emitters = PHASES[phase].patterns.iter()
+            .filter_map(|&(name, cannon)| patterns::pattern(name).map(|p| (Emitter::new(p), cannon)))
+            .collect();
+    }
+}
+
+/// The remaining health of the boss, between 0 and 1, unless it was
+/// defeated.
+pub fn health(world: &World, boss: Entity) -> Option<f64> {
+    match world.get::<Boss>(boss) {
+        Some(state) if !state.is_defeated() => {
+            Some(world.get::<Health>(boss).map_or(1.0, |&Health(hp)| hp as f64 / BOSS_HP as f64))
+        }
+        _ => None,
+    }
+}
+
+/// Flies the boss in, makes it hover and fire at the `Aim` while fighting,
+/// and blows it up once defeated, filling `Blasts`. The boss is despawned
+/// once done exploding.
+pub fn update(world: &mut World, phi: &mut Phi, dt: f64) {
+    let (w, h) = phi.output_size();
+    let target = match world.resource::<Aim>() {
+        Some(&Aim(Some(target))) => Some((target.x, target.y)),
+        _ => None,
+    };
+
+    let mut blasts = vec![];
+
+    for entity in world.with::<Boss>() {
+        let mut rect = match world.get::<Body>(entity) {
+            Some(&Body(rect)) => rect,
+            None => continue,
+        };
+
+        let hp = world.get::<Health>(entity).map(|&Health(hp)| hp);
+
+        // Taken out of the world while it is updated, so that its other
+        // components can change along the way.
+        let mut boss = world.remove::<Boss>(entity).unwrap();
+        boss.time += dt;
+
+        match boss.state {
+            State::Intro => {
+                match boss.intro.offset_at(boss.time) {
+                    Some((dx, dy)) => {
+                        rect.x = boss.intro_from.0 + dx;
+                        rect.y = boss.intro_from.1 + dy;
+                    }
+                    None => {
+                        boss.state = State::Fight;
+                        boss.time = 0.0;
+                        world.insert(entity, Health(BOSS_HP));
+                    }
+                }
+            }
+
+            State::Fight if hp == Some(0) => {
+                boss.state = State::Outro;
+                boss.time = 0.0;
+                world.remove::<Health>(entity);
+                world.remove::<Collider>(entity);
+            }
+
+            State::Fight => {
+                let health = hp.unwrap_or(BOSS_HP) as f64 / BOSS_HP as f64;
+                if let Some(phase) = PHASES.iter().rposition(|phase| health <= phase.threshold) {
+                    if phase > boss.phase {
+                        boss.enter_phase(phase);
+                    }
+                }
+
+                let phase = boss.time * 2.0 * ::std::f64::consts::PI / HOVER_PERIOD;
+                rect.x = w - BOSS_W - BOSS_MARGIN;
+                rect.y = ((h - BOSS_H) / 2.0 + phase.sin() * HOVER_AMPLITUDE)
+                    .max(0.0).min(h - BOSS_H);
+
+                // A dead player is not shot at anymore.
+                if let Some(target) = target {
+                    for &mut (ref mut emitter, cannon) in &mut boss.emitters {
+                        let origin = (rect.x + CANNONS[cannon].0, rect.y + CANNONS[cannon].1);
+                        for bullet in emitter.update(dt, origin, target) {
+                            bullet.spawn(world);
+                        }
+                    }
+                }
+            }
+
+            State::Outro if boss.time >= OUTRO_DURATION => {
+                // The last explosion covers the hull, rather than the empty
+                // corners of the sprite.
+                let hull = HITBOXES[1..].iter().fold(HITBOXES[0], |hull, &hitbox| hull.union(hitbox));
+                blasts.push(hull.translate(Vec2::new(rect.x, rect.y)));
+                world.despawn(entity);
+                continue;
+            }
+
+            State::Outro => {
+                // The boss sinks while small explosions go off all over it.
+                rect.y += 20.0 * dt;
+                boss.next_blast -= dt;
+
+                while boss.next_blast <= 0.0 {
+                    boss.next_blast += OUTRO_BLAST_INTERVAL;
+
+                    let hitbox = HITBOXES[::rand::random::<usize>() % HITBOXES.len()];
+                    let side = 48.0 + ::rand::random::<f64>() * 48.0;
+
+                    blasts.push(Rectangle {
+                        x: rect.x + hitbox.x + ::rand::random::<f64>() * hitbox.w - side / 2.0,
+                        y: rect.y + hitbox.y + ::rand::random::<f64>() * hitbox.h - side / 2.0,
+                        w: side,
+                        h: side,
+                    });
+                }
+            }
+        }
+
+        world.insert(entity, Body(rect));
+        world.insert(entity, boss);
+    }
+
+    world.insert_resource(Blasts(blasts));
+}
@@ -0,0 +1,185 @@
+use phi::Phi;
+use phi::data::{AlphaMask, Rectangle, Vec2};
+use phi::ecs::{Body, Collider, Drawable, Entity, FollowPath, Shape, World};
+use phi::gfx::Sprite;
+use phi::path::Path;
+use std::rc::Rc;
+use views::patterns::{self, Aim, Emitter};
+use views::weapons::Health;
+
+const ENEMIES_PATH: &'static str = "assets/enemies.png";
+const ENEMY_SIDE: f64 = 48.0;
+/// The collision layer of enemies.
+pub const ENEMY_LAYER: u32 = 4;
+
+/// The amplitude, in pixels, of the waves drawn by flyers.
+const WAVE_AMPLITUDE: f64 = 60.0;
+/// The horizontal distance covered by half a wave.
+const WAVE_HALF_LENGTH: f64 = 120.0;
+const WAVE_HALF_DURATION: f64 = 0.8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnemyKind {
+    /// Crosses the screen following a sine wave.
+    Flyer,
+    /// Stops for a moment, then swoops towards where the player was.
+    Diver,
+    /// Slides in, holds its position for a while, then drifts away.
+    Turret,
+}
+
+impl EnemyKind {
+    fn hp(self) -> u32 {
+        match self {
+            EnemyKind::Flyer => 2,
+            EnemyKind::Diver => 1,
+            EnemyKind::Turret => 6,
+        }
+    }
+
+    pub fn score(self) -> u32 {
+        match self {
+            EnemyKind::Flyer => 200,
+            EnemyKind::Diver => 250,
+            EnemyKind::Turret => 400,
+        }
+    }
+
+    /// The path followed by an enemy entering the screen from `origin`, which
+    /// may aim for `target`.
+    fn path(self, origin: (f64, f64), target: (f64, f64), screen_w: f64) -> Path {
+        match self {
+            EnemyKind::Flyer => {
+                // Every half wave is a curve bulging up or down, alternately.
+                let half_wave = |dir: f64| {
+                    Path::new().curve((-WAVE_HALF_LENGTH / 2.0, dir * WAVE_AMPLITUDE * 2.0),
+                                      -WAVE_HALF_LENGTH, 0.0, WAVE_HALF_DURATION)
+                };
+
+                let waves = ((screen_w + ENEMY_SIDE) / (2.0 * WAVE_HALF_LENGTH)).ceil() as usize;
+                half_wave(-1.0).then(half_wave(1.0)).repeat(waves)
+            }
+
+            EnemyKind::Diver => {
+                let entry = Path::new().line(-160.0, 0.0, 1.0).wait(0.6);
+                let (dx, dy) = (target.0 - (origin.0 - 160.0), target.1 - origin.1);
+
+                entry
+                    .curve((dx, 0.0), dx, dy, 1.2)
+                    .line(-screen_w, dy.signum() * 120.0, 2.0)
+            }
+
+            EnemyKind::Turret => {
+                Path::new()
+                    .line(-140.0, 0.0, 1.5)
+                    .wait(6.0)
+                    .line(-(screen_w + ENEMY_SIDE), 0.0, 10.0)
+            }
+        }
+    }
+
+    /// The name of the bullet pattern fired by this kind of enemy.
+    fn pattern(self) -> &'static str {
+        match self {
+            EnemyKind::Flyer => "aimed",
+            EnemyKind::Diver => "spread",
+            EnemyKind::Turret => "spiral",
+        }
+    }
+
+    fn sprite_index(self) -> usize {
+        match self {
+            EnemyKind::Flyer => 0,
+            EnemyKind::Diver => 1,
+            EnemyKind::Turret => 2,
+        }
+    }
+}
+
+/// Makes an entity an enemy of the given kind, which fires the bullets of
+/// its pattern, if any.
+pub struct Enemy {
+    pub kind: EnemyKind,
+    emitter: Option<Emitter>,
+}
+
+/// Fires the bullets of enemies at the `Aim`. Enemies only shoot once they
+/// are fully visible.
+pub fn fire(world: &mut World, phi: &mut Phi, dt: f64) {
+    let (w, _) = phi.output_size();
+    let target = match world.resource::<Aim>() {
+        Some(&Aim(Some(target))) => (target.x, target.y),
+        _ => return,
+    };
+
+    for entity in world.with::<Enemy>() {
+        let rect = match world.get::<Body>(entity) {
+            Some(&Body(rect)) => rect,
+            None => continue,
+        };
+
+        let origin = (rect.x, rect.y + rect.h / 2.0);
+        let bullets = match world.get_mut::<Enemy>(entity).unwrap().emitter {
+            Some(ref mut emitter) if rect.x + rect.w <= w => emitter.update(dt, origin, target),
+            _ => vec![],
+        };
+
+        for bullet in bullets {
+            bullet.spawn(world);
+        }
+    }
+}
+
+/// Loads the enemy sprites once and hands out enemies sharing them.
+pub struct EnemyFactory {
+    sprites: Vec<Sprite>,
+    masks: Vec<Rc<AlphaMask>>,
+}
+
+impl EnemyFactory {
+    pub fn new(phi: &mut Phi) -> EnemyFactory {
+        let spritesheet = Sprite::load(&mut phi.renderer, ENEMIES_PATH).unwrap();
+        let regions: Vec<Rectangle> = (0..3).map(|x| {
+            Rectangle {
+                w: ENEMY_SIDE,
+                h: ENEMY_SIDE,
+                x: ENEMY_SIDE * x as f64,
+                y: 0.0,
+            }
+        }).collect();
+
+        EnemyFactory {
+            sprites: regions.iter().map(|&region| spritesheet.region(region).unwrap()).collect(),
+            masks: regions.iter().map(|&region| Rc::new(AlphaMask::load(ENEMIES_PATH, region).unwrap())).collect(),
+        }
+    }
+
+    /// Spawns an enemy just off the right edge of the screen, `at` being a
+    /// fraction of the height of the screen. Enemies which aim for something
+    /// do so at `target`. They are despawned once their path is over.
+    pub fn spawn(&self, phi: &mut Phi, world: &mut World, kind: EnemyKind, at: f64, target: (f64, f64)) -> Entity {
+        let (w, h) = phi.output_size();
+        let origin = (w, at * (h - ENEMY_SIDE));
+
+        let enemy = world.spawn();
+        world.insert(enemy, Body(Rectangle {
+            x: origin.0,
+            y: origin.1,
+            w: ENEMY_SIDE,
+            h: ENEMY_SIDE,
+        }));
+        world.insert(enemy, FollowPath::new(Vec2::new(origin.0, origin.1), kind.path(origin, target, w)));
+        world.insert(enemy, Drawable::Sprite(self.sprites[kind.sprite_index()].clone()));
+        world.insert(enemy, Shape::Mask(self.masks[kind.sprite_index()].clone()));
+        world.insert(enemy, Health(kind.hp()));
+        world.insert(enemy, Collider {
+            layers: ENEMY_LAYER,
+            collides_with: 0,
+        });
+        world.insert(enemy, Enemy {
+            kind: kind,
+            emitter: patterns::pattern(kind.pattern()).map(Emitter::new),
+        });
+        enemy
+    }
+}
@@ -1,26 +1,110 @@
 use phi::{Phi, View, ViewAction};
-use phi::data::Rectangle;
-use phi::gfx::{Sprite, CopySprite, AnimatedSprite};
+use phi::audio::{Priority, Sound, Stem};
+use phi::ecs::{self, Body, Collider, Collisions, Contacts, Drawable, Entity, Lifetime, Schedule, Shape, World, ZOrder};
+use phi::data::{Polygon, Rectangle, Vec2};
+use phi::gfx::{Sprite, AnimatedSprite};
+use phi::particles::{self, Emission, Emitter, ParticleConfig};
 use std::path::Path;
 use std::option::Option;
+use rand::{SeedableRng, XorShiftRng};
 use sdl2::pixels::Color;
 use sdl2::render::{Renderer, Texture, TextureQuery};
 use sdl2::image::LoadTexture;
-use views::shared::BgSet;
+use views::asteroids::{Asteroid, AsteroidFactory, AsteroidSpawner, SpawnConfig, ASTEROID_LAYER};
+use views::boss::{self, Blasts, Boss, BOSS_LAYER, BOSS_SCORE};
+use views::enemies::{self, Enemy, EnemyFactory, ENEMY_LAYER};
+use views::hud::Hud;
+use views::level::{self, Event, Level, Timeline};
+use views::patterns::{self, Aim, ENEMY_BULLET_LAYER};
+use views::pause::{PauseAction, PauseMenu};
+use views::powerups::{self, PowerUpKind, PowerUps, POWERUP_LAYER};
+use views::score::Score;
+use views::shared::{Depth, Parallax};
+use views::weapons::{self, Arsenal, Health, Weapon};
 
 const PLAYER_SPEED: f64 = 180.0;
+const PLAYER_LIVES: u32 = 3;
+/// For how long, in seconds, the player cannot be hit after losing a life.
+const INVULNERABILITY_DURATION: f64 = 2.0;
+/// How many times per second the ship blinks while invulnerable.
+const BLINK_RATE: f64 = 10.0;
+/// How long to keep the game running after the player died, so that the
+/// explosion is visible before the game over screen shows up.
+const GAME_OVER_DELAY: f64 = 1.5;
+/// For how long, in seconds of real time, the game freezes when the ship is
+/// hit and when the boss is defeated, to make the impact felt.
+const HIT_STOP: f64 = 0.08;
+const BOSS_HIT_STOP: f64 = 0.35;
 const SHIP_W: f64 = 43.0;
 const SHIP_H: f64 = 39.0;
-const ASTEROID_PATH: &'static str = "assets/asteroid.png";
-const ASTEROIDS_WIDE: usize = 21;
-const ASTEROIDS_HIGH: usize = 7;
-const ASTEROIDS_TOTAL: usize = ASTEROIDS_WIDE * ASTEROIDS_HIGH - 4;
-const ASTEROID_SIDE: f64 = 96.0;
-const BULLET_SPEED: f64 = 240.0;
-const BULLET_W: f64 = 8.0;
-const BULLET_H: f64 = 4.0;
+/// The outline of the ship which can be hit, relative to its top-left corner.
+/// It is narrower than the sprite, so that near misses do not count.
+const SHIP_HULL: [(f64, f64); 5] = [(4.0, 8.0), (24.0, 10.0), (40.0, 19.5), (24.0, 29.0), (4.0, 31.0)];
+const EXPLOSION_PATH: &'static str = "assets/explosion.png";
+const EXPLOSIONS_WIDE: usize = 5;
+const EXPLOSIONS_HIGH: usize = 4;
+const EXPLOSIONS_TOTAL: usize = 19;
+const EXPLOSION_SIDE: f64 = 96.0;
+const EXPLOSION_FPS: f64 = 16.0;
+const EXPLOSION_DURATION: f64 = 1.0 / EXPLOSION_FPS * EXPLOSIONS_TOTAL as f64;
+const EXPLOSION_SOUND_PATH: &'static str = "assets/sfx/explosion.wav";
+const LEVEL_PATH: &'static str = "assets/levels/level1.txt";
+/// The scenery which the game starts with.
+const FIRST_SCENERY: &'static str = "stars";
+/// The size of the cells used to find what collides, about the size of the
+/// largest asteroids.
+const COLLISION_CELL: f64 = 96.0;
+/// The chance that a destroyed asteroid or enemy drops a power-up. Bosses
+/// always do.
+const ASTEROID_DROP_CHANCE: f64 = 0.08;
+const ENEMY_DROP_CHANCE: f64 = 0.25;
+const SHOT_SOUND_PATH: &'static str = "assets/sfx/shot.wav";
 const DEBUG: bool = false;
 
+/// The flames coming out of the back of the ship.
+const EXHAUST: ParticleConfig = ParticleConfig {
+    lifetime: (0.15, 0.35),
+    speed: (80.0, 140.0),
+    direction: ::std::f64::consts::PI,
+    spread: 0.25,
+    gravity: Vec2 { x: 0.0, y: 0.0 },
+    drag: 1.0,
+    colors: (Color::RGBA(255, 220, 120, 255), Color::RGBA(255, 60, 0, 0)),
+    sizes: (5.0, 1.0),
+};
+const EXHAUST_RATE: f64 = 80.0;
+const PARTICLE_PATH: &'static str = "assets/particle.png";
+
+/// The sparks and debris thrown around by explosions.
+const SPARKS: ParticleConfig = ParticleConfig {
+    lifetime: (0.4, 1.0),
+    speed: (40.0, 220.0),
+    direction: 0.0,
+    spread: particles::ALL_AROUND,
+    gravity: Vec2 { x: -30.0, y: 0.0 },
+    drag: 1.5,
+    colors: (Color::RGBA(255, 240, 180, 255), Color::RGBA(200, 60, 0, 0)),
+    sizes: (4.0, 1.0),
+};
+/// How many sparks an explosion throws per pixel of its size.
+const SPARKS_PER_PIXEL: f64 = 0.4;
+
+/// The layers of the in-game music, from the calmest to the most intense.
+const MUSIC_STEMS: [Stem; 3] = [
+    Stem { path: "assets/music/stem_base.wav", threshold: 0.0 },
+    Stem { path: "assets/music/stem_drums.wav", threshold: 0.2 },
+    Stem { path: "assets/music/stem_lead.wav", threshold: 0.6 },
+];
+
+/// How much every asteroid on screen adds to the intensity of the music.
+const ASTEROID_INTENSITY: f64 = 0.2;
+/// How much every enemy on screen adds to the intensity of the music.
+const ENEMY_INTENSITY: f64 = 0.15;
+/// Added to the intensity while a boss is around.
+const BOSS_INTENSITY: f64 = 0.5;
+/// Added to the intensity when the player is on their last life.
+const LAST_LIFE_INTENSITY: f64 = 0.4;
+
 #[derive(Clone, Copy)]
 enum ShipFrame {
     UpNorm = 0,
@@ -34,155 +118,199 @@ enum ShipFrame {
     DownSlow = 8,
 }
 
-#[derive(Clone, Copy)]
-struct RectBullet {
-    rect: Rectangle,
-}
-
+/// The player, whose position lives in the `Body` of its entity, and who is
+/// drawn by the world along with everything else.
 struct Ship {
-    rect: Rectangle,
+    entity: Entity,
     sprites: Vec<Sprite>,
     current: ShipFrame,
+    lives: u32,
+    // The time, in seconds, during which the ship cannot be hit anymore.
+    invulnerable: f64,
+    powers: PowerUps,
+    // The time left before the weapon can fire again.
+    cooldown: f64,
 }
 
-struct Asteroid {
+/// Loads the explosion sprite sheet once and hands out explosions sharing it.
+struct ExplosionFactory {
     sprite: AnimatedSprite,
-    rect: Rectangle,
-    vel: f64,
 }
 
 pub struct ShipView {
     player: Ship,
-    asteroid: Asteroid,
-    bullets: Vec<RectBullet>,
-    bg: BgSet,
+    asteroid_factory: AsteroidFactory,
+    asteroid_spawner: AsteroidSpawner,
+    enemy_factory: EnemyFactory,
+    timeline: Timeline,
+    boss: Option<Entity>,
+    arsenal: Arsenal,
+    // Holds the ship, asteroids, enemies, bullets, boss, explosions and
+    // power-ups.
+    world: World,
+    update_systems: Schedule,
+    render_systems: Schedule,
+    explosion_factory: ExplosionFactory,
+    exhaust: Emitter,
+    sparks: Emitter,
+    bg: Parallax,
+    // The backgrounds which `Event::Scenery` switches to, loaded along with
+    // the level so that switching cannot fail.
+    sceneries: Vec<Parallax>,
+    // How fast the background scrolls, relative to its normal speed, as set
+    // by the level. It applies on top of the time of the game, which already
+    // pauses and slows the background down along with everything else, and
+    // stays out of `phi.clock` so that speeding up the scenery does not speed
+    // up the enemies too.
+    bg_speed: f64,
+    shot_sound: Option<Sound>,
+    explosion_sound: Option<Sound>,
+    score: Score,
+    hud: Hud,
+    // Drives the spawns, the fragments of asteroids and the drops. Games
+    // started from the same seed are not reproducible, as the time elapsed
+    // between frames varies and cosmetic effects have their own randomness.
+    seed: u32,
+    rng: XorShiftRng,
+    // Counts down once the player has lost their last life.
+    game_over_in: Option<f64>,
+    // Shown while the game is paused.
+    pause: Option<PauseMenu>,
 }
 
 impl Ship {
-    fn spawn_bullets(&self) -> Vec<RectBullet> {
-        let cannons_x = self.rect.x + 30.0;
-        let cannon1_y = self.rect.y + 6.0;
-        let cannon2_y = self.rect.y + SHIP_H - 10.0;
-
-        vec![
-            RectBullet {
-                rect: Rectangle {
-                    x: cannons_x,
-                    y: cannon1_y,
-                    w: BULLET_W,
-                    h: BULLET_H,
-                }
-            },
-            RectBullet {
-                rect: Rectangle {
-                    x: cannons_x,
-                    y: cannon2_y,
-                    w: BULLET_W,
-                    h: BULLET_H,
-                }
-            }
-        ]
+    fn is_alive(&self) -> bool {
+        self.lives > 0
     }
-}
 
-impl RectBullet {
-    fn update(mut self, phi: &mut Phi, dt: f64) -> Option<Self> {
-        let (w, _) = phi.output_size();
-        self.rect.x += BULLET_SPEED * dt;
-
-        if self.rect.x > w {
-            None
-        } else {
-            Some(self)
-        }
+    fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
     }
 
-    fn render(self, phi: &mut Phi) {
-        phi.renderer.set_draw_color(Color::RGB(230, 230, 30));
-        phi.renderer.fill_rect(self.rect.to_sdl());
+    fn rect(&self, world: &World) -> Rectangle {
+        world.get::<Body>(self.entity).unwrap().0
     }
 
-    fn rect(&self) -> Rectangle {
-        self.rect
+    /// Whether the ship is drawn this frame. It blinks while invulnerable by
+    /// skipping every other period.
+    fn is_visible(&self) -> bool {
+        self.is_alive() && !(self.is_invulnerable() && (self.invulnerable * BLINK_RATE) as u32 % 2 == 1)
     }
-}
 
-impl Asteroid {
-    fn new(phi: &mut Phi) -> Asteroid {
-        let mut asteroid = 
-        Asteroid {
-            sprite: Asteroid::get_sprite(phi, 1.0),
-            rect: Rectangle {
-                w: 0.0,
-                h: 0.0,
-                x: 0.0,
-                y: 0.0,
-            },
-            vel: 0.0,
-        };
-
-        asteroid.reset(phi);
-        asteroid
+    fn is_shielded(&self) -> bool {
+        self.powers.is_shielded()
     }
 
-    fn reset(&mut self, phi: &mut Phi) {
-        let (w, h) = phi.output_size();
-
-        self.sprite.set_fps(::rand::random::<f64>().abs() * 20.0 + 10.0);
+    /// Takes a life away from the player unless they are shielded or still
+    /// invulnerable from the previous hit. Returns whether the ship was hurt.
+    fn hit(&mut self) -> bool {
+        if !self.is_alive() || self.is_shielded() || self.is_invulnerable() {
+            return false;
+        }
 
-        self.rect = Rectangle {
-            w: ASTEROID_SIDE,
-            h: ASTEROID_SIDE,
-            x: w,
-            y: ::rand::random::<f64>().abs() * (h - ASTEROID_SIDE),
-        };
+        self.lives -= 1;
+        self.invulnerable = INVULNERABILITY_DURATION;
+        self.powers.clear();
+        true
+    }
 
-        self.vel = ::rand::random::<f64>().abs() * 100.0 + 50.0;
+    /// Gives the ship the sprite of its current frame, or none at all while
+    /// it is hidden.
+    fn show(&self, world: &mut World) {
+        if self.is_visible() {
+            world.insert(self.entity, Drawable::Sprite(self.sprites[self.current as usize].clone()));
+        } else {
+            world.remove::<Drawable>(self.entity);
+        }
     }
 
-    fn update(&mut self, phi: &mut Phi, dt: f64) {
-        self.rect.x -= dt * self.vel;
-        self.sprite.add_time(dt);
-        if self.rect.x <= -ASTEROID_SIDE {
-            self.reset(phi);
+    fn render_shield(&self, phi: &mut Phi, rect: Rectangle) {
+        if !self.is_visible() {
+            return;
+        }
+
+        // The shield flickers when it is about to run out.
+        let shield_left = self.powers.shield_left();
+        if shield_left > 2.0 || (shield_left > 0.0 && (shield_left * BLINK_RATE) as u32 % 2 == 0) {
+            phi.renderer.set_draw_color(Color::RGB(80, 255, 160));
+            let _ = phi.renderer.draw_rect(Rectangle {
+                x: rect.x - 6.0,
+                y: rect.y - 6.0,
+                w: rect.w + 12.0,
+                h: rect.h + 12.0,
+            }.to_sdl());
         }
     }
 
-    fn render(&mut self, phi: &mut Phi) {
-        phi.renderer.copy_sprite(&self.sprite, self.rect);
+    /// Whether the ship should fire this frame. Holding space fires
+    /// repeatedly, as fast as the weapon allows, but not while the game is
+    /// stopped by a freeze frame.
+    fn trigger(&mut self, phi: &mut Phi, dt: f64, weapon: &dyn Weapon) -> bool {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+
+        if dt > 0.0 && phi.events.key_space && self.cooldown == 0.0 {
+            self.cooldown = weapon.cooldown();
+            true
+        } else {
+            false
+        }
     }
+}
 
-    fn get_sprite(phi: &mut Phi, fps: f64) -> AnimatedSprite {
-        let asteroid_spritesheet = Sprite::load(&mut phi.renderer, ASTEROID_PATH).unwrap();
-        let mut asteroid_sprites = Vec::with_capacity(ASTEROIDS_TOTAL);
+impl ExplosionFactory {
+    fn new(phi: &mut Phi) -> ExplosionFactory {
+        let explosion_spritesheet = Sprite::load(&mut phi.renderer, EXPLOSION_PATH).unwrap();
+        let mut explosion_sprites = Vec::with_capacity(EXPLOSIONS_TOTAL);
 
-        for yth in 0..ASTEROIDS_HIGH {
-            for xth in 0..ASTEROIDS_WIDE {
-                if ASTEROIDS_WIDE * yth + xth >= ASTEROIDS_TOTAL {
+        for yth in 0..EXPLOSIONS_HIGH {
+            for xth in 0..EXPLOSIONS_WIDE {
+                if EXPLOSIONS_WIDE * yth + xth >= EXPLOSIONS_TOTAL {
                     break;
                 }
 
-                asteroid_sprites.push(
-                    asteroid_spritesheet.region(Rectangle {
-                        w: ASTEROID_SIDE,
-                        h: ASTEROID_SIDE,
-                        x: ASTEROID_SIDE * xth as f64,
-                        y: ASTEROID_SIDE * yth as f64,
+                explosion_sprites.push(
+                    explosion_spritesheet.region(Rectangle {
+                        w: EXPLOSION_SIDE,
+                        h: EXPLOSION_SIDE,
+                        x: EXPLOSION_SIDE * xth as f64,
+                        y: EXPLOSION_SIDE * yth as f64,
                     }).unwrap());
             }
         }
 
-        AnimatedSprite::with_fps(asteroid_sprites, fps)
+        ExplosionFactory {
+            sprite: AnimatedSprite::with_fps(explosion_sprites, EXPLOSION_FPS),
+        }
+    }
+
+    /// Spawns an explosion of the given size, centered on `center`, which
+    /// goes away once its animation is over.
+    fn at_center(&self, world: &mut World, center: Vec2, side: f64) -> Entity {
+        let explosion = world.spawn();
+        world.insert(explosion, Body(Rectangle::from_center(center, side, side)));
+        world.insert(explosion, Drawable::Animated(self.sprite.clone()));
+        world.insert(explosion, Lifetime(EXPLOSION_DURATION));
+        world.insert(explosion, ZOrder(1));
+        explosion
     }
 }
 
 impl ShipView {
-    pub fn new(phi: &mut Phi) -> ShipView {
-        let bg = BgSet::new(&mut phi.renderer);
+    pub fn new(phi: &mut Phi) -> Result<ShipView, String> {
+        let layers = level::load_scenery(FIRST_SCENERY)?;
+        let bg = Parallax::new(&mut phi.renderer, &layers)?;
         ShipView::with_background(phi, bg)
     }
 
-    pub fn with_background(phi: &mut Phi, bg: BgSet) -> ShipView {
+    /// Fails if the level, or one of its sceneries, cannot be loaded.
+    pub fn with_background(phi: &mut Phi, bg: Parallax) -> Result<ShipView, String> {
+        let level = Level::load(LEVEL_PATH)?;
+
+        let mut sceneries = vec![];
+        for layers in level.sceneries() {
+            sceneries.push(Parallax::new(&mut phi.renderer, layers)?);
+        }
+
         // Get the spaceship's sprites
         let spritesheet = Sprite::load(&mut phi.renderer, "assets/spaceship.png").unwrap();
         let mut sprites = Vec::with_capacity(9);
@@ -198,52 +326,223 @@ impl ShipView {
             }
         }
 
-        ShipView {
+        phi.audio.play_layers(&MUSIC_STEMS);
+
+        let seed = ::rand::random::<u32>();
+
+        let mut world = World::new();
+        let player_entity = world.spawn();
+        world.insert(player_entity, Body(Rectangle {
+            x: 64.0,
+            y: 64.0,
+            w: SHIP_W,
+            h: SHIP_H,
+        }));
+        world.insert(player_entity, Shape::Polygon(Polygon::new(SHIP_HULL.to_vec())));
+        world.insert(player_entity, Collider {
+            layers: 0,
+            collides_with: ASTEROID_LAYER | ENEMY_LAYER | BOSS_LAYER | ENEMY_BULLET_LAYER | POWERUP_LAYER,
+        });
+
+        Ok(ShipView {
             player: Ship {
-                rect: Rectangle {
-                    x: 64.0,
-                    y: 64.0,
-                    w: SHIP_W,
-                    h: SHIP_H,
-                },
+                entity: player_entity,
                 sprites: sprites,
                 current: ShipFrame::MidNorm,
+                lives: PLAYER_LIVES,
+                invulnerable: 0.0,
+                powers: PowerUps::new(),
+                cooldown: 0.0,
             },
 
-            bullets: vec![],
+            arsenal: Arsenal::new(phi),
+
+            asteroid_factory: AsteroidFactory::new(phi),
+
+            asteroid_spawner: AsteroidSpawner::new(SpawnConfig::default()),
+
+            enemy_factory: EnemyFactory::new(phi),
+
+            timeline: Timeline::new(level),
+
+            boss: None,
+
+            world: world,
 
-            asteroid: Asteroid::new(phi),
+            update_systems: Schedule::new()
+                .with(weapons::discharge)
+                .with(weapons::home)
+                .with(weapons::weave)
+                .with(patterns::thrust)
+                .with(ecs::paths)
+                .with(enemies::fire)
+                .with(boss::update)
+                .with(ecs::movement)
+                .with(ecs::aging)
+                .with(Collisions::new(COLLISION_CELL)),
+
+            render_systems: Schedule::new().with(ecs::render),
+
+            explosion_factory: ExplosionFactory::new(phi),
+
+            exhaust: Emitter::new(EXHAUST, Emission::Continuous(EXHAUST_RATE), 64)
+                .with_sprite(Sprite::load(&mut phi.renderer, PARTICLE_PATH).unwrap()),
+
+            sparks: Emitter::new(SPARKS, Emission::Burst, 1024),
 
             bg: bg,
+
+            sceneries: sceneries,
+
+            bg_speed: 1.0,
+
+            shot_sound: phi.audio.load_sound(SHOT_SOUND_PATH),
+
+            explosion_sound: phi.audio.load_sound(EXPLOSION_SOUND_PATH),
+
+            score: Score::new(),
+
+            hud: Hud::new(),
+
+            seed: seed,
+
+            // The constant words only make sure the seed is never all zeroes.
+            rng: XorShiftRng::from_seed([seed, 0x193a_6754, 0xa8a7_d469, 0x9783_0e05]),
+
+            game_over_in: None,
+            pause: None,
+        })
+    }
+
+    /// Switches to the game, or back to the main menu with the error if it
+    /// could not be created.
+    pub fn start(phi: &mut Phi, game: Result<ShipView, String>) -> ViewAction {
+        match game {
+            Ok(game) => ViewAction::ChangeView(Box::new(game)),
+            Err(e) => {
+                eprintln!("Could not start the game: {}", e);
+                let menu = ::views::main_menu::MainMenuView::with_error(phi, &e);
+                ViewAction::ChangeView(Box::new(menu))
+            }
+        }
+    }
+
+    /// How hectic the game currently is, between 0 and 1, which drives the
+    /// layers of the music.
+    fn intensity(&self, phi: &mut Phi) -> f64 {
+        let (w, h) = phi.output_size();
+        let screen = Rectangle { x: 0.0, y: 0.0, w: w, h: h };
+
+        let world = &self.world;
+        let on_screen = |entity| world.get::<Body>(entity).map_or(false, |&Body(rect)| screen.overlaps(rect));
+
+        let asteroids = world.with::<Asteroid>().into_iter().filter(|&entity| on_screen(entity)).count() as f64;
+        let enemies = world.with::<Enemy>().into_iter().filter(|&entity| on_screen(entity)).count() as f64;
+        let boss = if self.boss.is_some() { BOSS_INTENSITY } else { 0.0 };
+        let last_life = if self.player.lives == 1 { LAST_LIFE_INTENSITY } else { 0.0 };
+
+        (asteroids * ASTEROID_INTENSITY + enemies * ENEMY_INTENSITY + boss + last_life).min(1.0)
+    }
+
+    fn explode(&mut self, phi: &mut Phi, rect: Rectangle) {
+        let center = rect.center();
+        self.explosion_factory.at_center(&mut self.world, center, rect.w.max(rect.h));
+        self.sparks.burst(center, (rect.w.max(rect.h) * SPARKS_PER_PIXEL) as usize);
+
+        if let Some(sound) = self.explosion_sound {
+            phi.audio.play_at(sound, center.x, center.y, Priority::Normal);
+        }
+    }
+
+    /// Draws the game as it currently is, without advancing it.
+    fn draw(&mut self, phi: &mut Phi) {
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        self.bg.render(&mut phi.renderer, Depth::Behind);
+
+        let rect = self.player.rect(&self.world);
+
+        if DEBUG {
+            phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
+            phi.renderer.fill_rect(rect.to_sdl());
+        }
+
+        self.exhaust.render(phi);
+        self.render_systems.run(&mut self.world, phi, 0.0);
+        self.player.render_shield(phi, rect);
+        self.sparks.render(phi);
+
+        self.bg.render(&mut phi.renderer, Depth::InFront);
+
+        self.hud.render(phi, &self.score, self.player.lives, phi.clock.real_elapsed());
+        self.hud.render_powerups(phi, &self.player.powers.active());
+
+        if let Some(health) = self.boss.and_then(|boss| boss::health(&self.world, boss)) {
+            self.hud.render_boss_health(phi, health);
         }
     }
 }
 
 impl View for ShipView {
     fn render(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
-        if phi.events.now.quit || phi.events.now.key_escape == Some(true) {
+        if phi.events.now.quit {
             return ViewAction::Quit;
         }
 
-        let diagonal = (phi.events.key_up ^ phi.events.key_down) &&
-                       (phi.events.key_left ^ phi.events.key_right);
+        // Escape pauses the game, after which the pause menu takes over the
+        // input until the game is resumed.
+        let paused = match self.pause.as_mut().map(|menu| menu.update(phi)) {
+            Some(PauseAction::Restart) => {
+                let game = ShipView::with_background(phi, self.bg.clone());
+                return ShipView::start(phi, game);
+            }
+            Some(PauseAction::QuitToMenu) => {
+                return ViewAction::ChangeView(Box::new(::views::main_menu::MainMenuView::new(phi)));
+            }
+            Some(PauseAction::Resume) => {
+                self.pause = None;
+                phi.clock.resume();
+                true
+            }
+            Some(PauseAction::None) => true,
+            None if phi.events.now.key_escape == Some(true) => {
+                phi.clock.pause();
+                self.pause = Some(PauseMenu::new(phi));
+                true
+            }
+            None => false,
+        };
+
+        // The game is still drawn, frozen, behind the menu.
+        if paused {
+            self.draw(phi);
 
-        let moved = if diagonal { 1.0 / 2.0f64.sqrt() } else { 1.0 } * PLAYER_SPEED * elapsed;
+            if let Some(ref menu) = self.pause {
+                menu.render(phi);
+            }
 
-        let dx = match (phi.events.key_left, phi.events.key_right) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
-        };
+            return ViewAction::None;
+        }
 
-        let dy = match (phi.events.key_up, phi.events.key_down) {
-            (true, true) | (false, false) => 0.0,
-            (true, false) => -moved,
-            (false, true) => moved,
+        // During bullet time, the ship keeps its pace while everything else
+        // slows down: it moves, fires and blinks in real time, so that the
+        // player gets to shoot more while the enemies are slowed down. Its
+        // power-ups still run out in game time. Pauses and freeze frames stop
+        // the ship along with the game.
+        let scale = phi.clock.scale();
+        let ship_elapsed = if scale > 0.0 { elapsed / scale } else { elapsed };
+
+        let axis = |negative: bool, positive: bool| match (negative, positive) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
         };
 
-        self.player.rect.x += dx;
-        self.player.rect.y += dy;
+        // Moving diagonally is no faster than moving straight.
+        let direction = Vec2::new(axis(phi.events.key_left, phi.events.key_right),
+                                  axis(phi.events.key_up, phi.events.key_down)).normalized();
+        let moved = PLAYER_SPEED * self.player.powers.speed_factor() * ship_elapsed;
 
         let movable_region = Rectangle {
             x: 0.0,
@@ -252,62 +551,206 @@ impl View for ShipView {
             h: phi.output_size().1,
         };
 
-        self.player.rect = self.player.rect.move_inside(movable_region).unwrap();
+        let rect = self.player.rect(&self.world).translate(direction * moved)
+            .move_inside(movable_region).unwrap();
+        self.world.insert(self.player.entity, Body(rect));
 
-        self.player.current = if dx == 0.0 && dy < 0.0 {
+        let (win_w, win_h) = phi.output_size();
+        phi.audio.set_listener(Rectangle {
+            x: 0.0,
+            y: 0.0,
+            w: win_w,
+            h: win_h,
+        });
+
+        self.player.current = if direction.x == 0.0 && direction.y < 0.0 {
             ShipFrame::UpNorm
-        } else if dx > 0.0 && dy < 0.0 {
+        } else if direction.x > 0.0 && direction.y < 0.0 {
             ShipFrame::UpFast
-        } else if dx < 0.0 && dy < 0.0 {
+        } else if direction.x < 0.0 && direction.y < 0.0 {
             ShipFrame::UpSlow
-        } else if dx == 0.0 && dy == 0.0 {
+        } else if direction.x == 0.0 && direction.y == 0.0 {
             ShipFrame::MidNorm
-        } else if dx > 0.0 && dy == 0.0 {
+        } else if direction.x > 0.0 && direction.y == 0.0 {
             ShipFrame::MidFast
-        } else if dx < 0.0 && dy == 0.0 {
+        } else if direction.x < 0.0 && direction.y == 0.0 {
             ShipFrame::MidSlow
-        } else if dx == 0.0 && dy > 0.0 {
+        } else if direction.x == 0.0 && direction.y > 0.0 {
             ShipFrame::DownNorm
-        } else if dx > 0.0 && dy > 0.0 {
+        } else if direction.x > 0.0 && direction.y > 0.0 {
             ShipFrame::DownFast
-        } else if dx < 0.0 && dy > 0.0 {
+        } else if direction.x < 0.0 && direction.y > 0.0 {
             ShipFrame::DownSlow
         } else {
             unreachable!()
         };
 
-        self.bullets =
-            self.bullets.iter()
-            .filter_map(|bullet| bullet.update(phi, elapsed))
-            .collect();
+        let alive = self.world.with::<Asteroid>().len();
+        for size in self.asteroid_spawner.update(&mut self.rng, elapsed, alive) {
+            self.asteroid_factory.random(phi, &mut self.world, &mut self.rng, size);
+        }
+
+        // Enemies and the boss shoot at the center of the ship, as long as it
+        // is alive.
+        let aim = if self.player.is_alive() { Some(rect.center()) } else { None };
+        self.world.insert_resource(Aim(aim));
 
-        self.asteroid.update(phi, elapsed);
+        self.update_systems.run(&mut self.world, phi, elapsed);
+
+        self.exhaust.set_position(Vec2::new(rect.x + 4.0, rect.y + rect.h / 2.0));
+        self.exhaust.set_active(self.player.is_alive());
+        self.bg.update(elapsed * self.bg_speed);
+        self.exhaust.update(elapsed);
+        self.sparks.update(elapsed);
+
+        self.player.powers.update(elapsed);
+        phi.clock.set_scale(self.player.powers.time_scale());
+
+        let blasts = match self.world.resource::<Blasts>() {
+            Some(&Blasts(ref blasts)) => blasts.clone(),
+            None => vec![],
+        };
 
-        if phi.events.now.key_space == Some(true) {
-            self.bullets.append(&mut self.player.spawn_bullets());
+        for blast in blasts {
+            self.explode(phi, blast);
         }
 
-        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
-        phi.renderer.clear();
+        // The level goes on once the boss is done exploding.
+        if self.boss.map_or(false, |boss| !self.world.is_alive(boss)) {
+            self.boss = None;
+            self.timeline.resume();
+        }
 
-        self.bg.back.render(&mut phi.renderer, elapsed);
-        self.bg.middle.render(&mut phi.renderer, elapsed);
+        for event in self.timeline.update(elapsed) {
+            match event {
+                Event::Enemy { kind, at } => {
+                    self.enemy_factory.spawn(phi, &mut self.world, kind, at, (rect.x, rect.y));
+                }
+                Event::Asteroid { size, at } => {
+                    self.asteroid_factory.at(phi, &mut self.world, &mut self.rng, size, at);
+                }
+                Event::Asteroids(config) => self.asteroid_spawner = AsteroidSpawner::new(config),
+                Event::BackgroundSpeed(speed) => self.bg_speed = speed,
+                Event::Scenery(index) => self.bg = self.sceneries[index].clone(),
+                Event::Boss => self.boss = Some(Boss::spawn(phi, &mut self.world)),
+            }
+        }
 
-        if DEBUG {
-            phi.renderer.set_draw_color(Color::RGB(200, 200, 50));
-            phi.renderer.fill_rect(self.player.rect.to_sdl());
+        self.score.update(elapsed);
+
+        // Every projectile damages the first asteroid, enemy or boss it hits
+        // and is consumed, unless it pierces through everything it hits.
+        let mut destroyed = weapons::hit(&mut self.world);
+
+        self.player.invulnerable = (self.player.invulnerable - ship_elapsed).max(0.0);
+
+        let touched: Vec<Entity> = match self.world.resource::<Contacts>() {
+            Some(&Contacts(ref contacts)) => contacts.iter()
+                .filter(|&&(a, _)| a == self.player.entity)
+                .map(|&(_, b)| b)
+                .collect(),
+            None => vec![],
+        };
+
+        let mut player_hit = false;
+
+        for entity in touched {
+            let layers = match self.world.get::<Collider>(entity) {
+                Some(collider) => collider.layers,
+                None => continue,
+            };
+
+            if layers & (ASTEROID_LAYER | ENEMY_LAYER) != 0 {
+                // Flying into an asteroid or an enemy destroys it, at the
+                // cost of one life unless the ship is shielded.
+                if self.world.get::<Health>(entity).map_or(true, |&Health(hp)| hp == 0) {
+                    continue;
+                }
+
+                let shielded = self.player.is_shielded();
+                if shielded || self.player.hit() {
+                    self.world.insert(entity, Health(0));
+                    destroyed.push(entity);
+                    player_hit |= !shielded;
+                }
+            } else if layers & BOSS_LAYER != 0 {
+                // Bosses are too sturdy to be rammed.
+                player_hit |= self.player.hit();
+            } else if layers & ENEMY_BULLET_LAYER != 0 {
+                // Enemy bullets are consumed by the ship, even while it is
+                // invulnerable.
+                self.world.despawn(entity);
+                player_hit |= self.player.hit();
+            } else if let Some(&kind) = self.world.get::<PowerUpKind>(entity) {
+                self.player.powers.collect(kind);
+                self.world.despawn(entity);
+            }
+        }
+
+        // Whatever was destroyed this frame scores, whether it was shot or
+        // rammed, before a hit breaks the combo.
+        for entity in destroyed {
+            let rect = match self.world.get::<Body>(entity) {
+                Some(&Body(rect)) => rect,
+                None => continue,
+            };
+
+            if let Some(size) = self.world.get::<Asteroid>(entity).map(|asteroid| asteroid.size) {
+                self.score.kill(size.score());
+                self.explode(phi, rect);
+                powerups::drop(&mut self.world, &mut self.rng, rect, ASTEROID_DROP_CHANCE);
+                self.asteroid_factory.fragments(&mut self.world, &mut self.rng, entity);
+                self.world.despawn(entity);
+            } else if let Some(kind) = self.world.get::<Enemy>(entity).map(|enemy| enemy.kind) {
+                self.score.kill(kind.score());
+                self.explode(phi, rect);
+                powerups::drop(&mut self.world, &mut self.rng, rect, ENEMY_DROP_CHANCE);
+                self.world.despawn(entity);
+            } else if self.world.get::<Boss>(entity).is_some() {
+                // The boss blows up by itself over the next few seconds.
+                self.score.kill(BOSS_SCORE);
+                phi.clock.freeze(BOSS_HIT_STOP);
+                powerups::drop(&mut self.world, &mut self.rng, rect, 1.0);
+            }
+        }
+
+        if player_hit {
+            self.score.reset_combo();
+            phi.clock.freeze(HIT_STOP);
+
+            if !self.player.is_alive() {
+                self.world.remove::<Collider>(self.player.entity);
+                self.explode(phi, rect);
+                self.game_over_in = Some(GAME_OVER_DELAY);
+            }
         }
 
-        phi.renderer.copy_sprite(&self.player.sprites[self.player.current as usize], self.player.rect);
+        self.world.maintain();
+
+        if let Some(delay) = self.game_over_in {
+            if delay <= 0.0 {
+                return ViewAction::ChangeView(Box::new(
+                    ::views::game_over::GameOverView::new(phi, self.score.points(), self.seed)));
+            }
 
-        for bullet in &self.bullets {
-            bullet.render(phi);
+            self.game_over_in = Some(delay - elapsed);
         }
 
-        self.asteroid.render(phi);
+        let intensity = self.intensity(phi);
+        phi.audio.set_intensity(intensity);
 
-        self.bg.front.render(&mut phi.renderer, elapsed);
+        let weapon = self.arsenal.get(self.player.powers.weapon());
+
+        if self.player.trigger(phi, ship_elapsed, weapon) && self.player.is_alive() {
+            weapon.fire(&mut self.world, rect);
+
+            if let Some(sound) = self.shot_sound {
+                phi.audio.play_at(sound, rect.x + rect.w / 2.0, rect.y + rect.h / 2.0, Priority::Low);
+            }
+        }
 
+        self.player.show(&mut self.world);
+        self.draw(phi);
         ViewAction::None
     }
 }
@@ -0,0 +1,168 @@
+use phi::{Phi, View, ViewAction};
+use phi::data::Rectangle;
+use phi::gfx::{CopySprite, Sprite};
+use sdl2::pixels::Color;
+use views::highscores::{HighScores, INITIALS_LEN};
+
+const FONT_PATH: &'static str = "assets/belligerent.ttf";
+const LETTERS: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Lets the player pick their initials arcade-style: up and down cycle through
+/// the letters, left and right move between them.
+struct InitialsEntry {
+    letters: [usize; INITIALS_LEN],
+    cursor: usize,
+    idle_sprites: Vec<Sprite>,
+    selected_sprites: Vec<Sprite>,
+}
+
+impl InitialsEntry {
+    fn new(phi: &mut Phi) -> InitialsEntry {
+        let mut idle_sprites = Vec::with_capacity(LETTERS.len());
+        let mut selected_sprites = Vec::with_capacity(LETTERS.len());
+
+        for letter in LETTERS {
+            let text = (*letter as char).to_string();
+            idle_sprites.push(phi.ttf_str_sprite(&text, FONT_PATH, 40, Color::RGB(220, 220, 220)).unwrap());
+            selected_sprites.push(phi.ttf_str_sprite(&text, FONT_PATH, 40, Color::RGB(255, 255, 0)).unwrap());
+        }
+
+        InitialsEntry {
+            letters: [0; INITIALS_LEN],
+            cursor: 0,
+            idle_sprites: idle_sprites,
+            selected_sprites: selected_sprites,
+        }
+    }
+
+    fn initials(&self) -> String {
+        self.letters.iter().map(|&l| LETTERS[l] as char).collect()
+    }
+
+    fn update(&mut self, phi: &mut Phi) {
+        let letter = &mut self.letters[self.cursor];
+
+        if phi.events.now.key_up == Some(true) {
+            *letter = (*letter + 1) % LETTERS.len();
+        }
+
+        if phi.events.now.key_down == Some(true) {
+            *letter = (*letter + LETTERS.len() - 1) % LETTERS.len();
+        }
+
+        if phi.events.now.key_right == Some(true) && self.cursor + 1 < INITIALS_LEN {
+            self.cursor += 1;
+        }
+
+        if phi.events.now.key_left == Some(true) && self.cursor > 0 {
+            self.cursor -= 1;
+        }
+    }
+
+    fn render(&self, phi: &mut Phi, y: f64) {
+        let (win_w, _) = phi.output_size();
+        let slot_w = 40.0;
+        let left = (win_w - slot_w * INITIALS_LEN as f64) / 2.0;
+
+        for (i, &letter) in self.letters.iter().enumerate() {
+            let sprite = if i == self.cursor {
+                &self.selected_sprites[letter]
+            } else {
+                &self.idle_sprites[letter]
+            };
+
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(sprite, Rectangle {
+                x: left + slot_w * i as f64 + (slot_w - w) / 2.0,
+                y: y,
+                w: w,
+                h: h,
+            });
+        }
+    }
+}
+
+pub struct GameOverView {
+    title: Sprite,
+    score_sprite: Sprite,
+    hint: Sprite,
+    score: u32,
+    seed: u32,
+    // Only present if the score made it into the high-score table.
+    entry: Option<InitialsEntry>,
+}
+
+impl GameOverView {
+    pub fn new(phi: &mut Phi, score: u32, seed: u32) -> GameOverView {
+        let entry = if HighScores::load().qualifies(score) {
+            Some(InitialsEntry::new(phi))
+        } else {
+            None
+        };
+
+        let hint = if entry.is_some() {
+            "New high score! Enter your initials"
+        } else {
+            "Press space to continue"
+        };
+
+        GameOverView {
+            title: phi.ttf_str_sprite("Game Over", FONT_PATH, 48, Color::RGB(255, 80, 80)).unwrap(),
+            score_sprite: phi.ttf_str_sprite(&format!("Score: {}", score), FONT_PATH, 32, Color::RGB(220, 220, 220)).unwrap(),
+            hint: phi.ttf_str_sprite(hint, FONT_PATH, 20, Color::RGB(160, 160, 160)).unwrap(),
+            score: score,
+            seed: seed,
+            entry: entry,
+        }
+    }
+}
+
+impl View for GameOverView {
+    fn render(&mut self, phi: &mut Phi, _: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if let Some(ref mut entry) = self.entry {
+            entry.update(phi);
+
+            if phi.events.now.key_space == Some(true) {
+                // Reload the table in case it changed since the game ended.
+                let mut table = HighScores::load();
+                table.insert(&entry.initials(), self.score, self.seed);
+
+                if let Err(e) = table.save() {
+                    eprintln!("Could not save the high scores: {}", e);
+                }
+
+                return ViewAction::ChangeView(Box::new(::views::highscores::HighScoreView::new(phi)));
+            }
+        } else if phi.events.now.key_space == Some(true) || phi.events.now.key_escape == Some(true) {
+            return ViewAction::ChangeView(Box::new(::views::main_menu::MainMenuView::new(phi)));
+        }
+
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        let (win_w, win_h) = phi.output_size();
+        let mut y = win_h / 4.0;
+
+        for sprite in &[&self.title, &self.score_sprite, &self.hint] {
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(*sprite, Rectangle {
+                x: (win_w - w) / 2.0,
+                y: y,
+                w: w,
+                h: h,
+            });
+
+            y += h + 24.0;
+        }
+
+        if let Some(ref entry) = self.entry {
+            entry.render(phi, y);
+        }
+
+        ViewAction::None
+    }
+}
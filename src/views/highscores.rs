@@ -0,0 +1,333 @@
+use phi::{Phi, View, ViewAction};
+use phi::data::Rectangle;
+use phi::gfx::{CopySprite, Sprite};
+use sdl2::pixels::Color;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::str;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HIGHSCORES_PATH: &'static str = "highscores.txt";
+const FONT_PATH: &'static str = "assets/belligerent.ttf";
+/// The amount of entries kept in the table.
+const MAX_ENTRIES: usize = 10;
+pub const INITIALS_LEN: usize = 3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub initials: String,
+    pub score: u32,
+    /// Seconds since the UNIX epoch at which the score was achieved.
+    pub date: u64,
+    /// The seed the game was started with.
+    pub seed: u32,
+}
+
+impl Entry {
+    /// Parses a line of the form `ABC 12345 1500000000 42`, returning `None`
+    /// if anything is off.
+    fn parse(line: &str) -> Option<Entry> {
+        let mut fields = line.split_whitespace();
+
+        let initials = match fields.next() {
+            Some(initials) if is_valid_initials(initials) => initials.to_string(),
+            _ => return None,
+        };
+
+        let score = fields.next().and_then(|f| f.parse().ok());
+        let date = fields.next().and_then(|f| f.parse().ok());
+        let seed = fields.next().and_then(|f| f.parse().ok());
+
+        if fields.next().is_some() {
+            return None;
+        }
+
+        match (score, date, seed) {
+            (Some(score), Some(date), Some(seed)) => Some(Entry {
+                initials: initials,
+                score: score,
+                date: date,
+                seed: seed,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn is_valid_initials(initials: &str) -> bool {
+    initials.len() == INITIALS_LEN && initials.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Formats a UNIX timestamp as `YYYY-MM-DD`.
+fn format_date(timestamp: u64) -> String {
+    // Converts a number of days since 1970-01-01 to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = (timestamp / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The best scores ever achieved, sorted from the highest to the lowest.
+pub struct HighScores {
+    entries: Vec<Entry>,
+}
+
+impl HighScores {
+    /// Loads the table from disk. A missing file yields an empty table, while
+    /// lines which cannot be read are skipped so that a damaged file does not
+    /// cost the player every other entry.
+    pub fn load() -> HighScores {
+        match File::open(HIGHSCORES_PATH) {
+            Ok(file) => HighScores::read(BufReader::new(file)),
+            Err(_) => HighScores { entries: vec![] },
+        }
+    }
+
+    fn read<R: BufRead>(reader: R) -> HighScores {
+        let mut entries = vec![];
+
+        for line in reader.split(b'\n') {
+            let line = match line {
+                Ok(line) => line,
+                // The file cannot be read any further.
+                Err(_) => break,
+            };
+
+            // Lines which are not valid UTF-8 are as damaged as any other.
+            if let Some(entry) = str::from_utf8(&line).ok().and_then(Entry::parse) {
+                entries.push(entry);
+            }
+        }
+
+        let mut table = HighScores { entries: entries };
+        table.normalize();
+        table
+    }
+
+    /// Writes the table to a temporary file first, so that a crash while
+    /// saving cannot leave a truncated table behind.
+    pub fn save(&self) -> ::std::io::Result<()> {
+        let tmp_path = format!("{}.tmp", HIGHSCORES_PATH);
+
+        {
+            let mut file = File::create(&tmp_path)?;
+            for entry in &self.entries {
+                writeln!(file, "{} {} {} {}", entry.initials, entry.score, entry.date, entry.seed)?;
+            }
+            file.sync_all()?;
+        }
+
+        fs::rename(&tmp_path, HIGHSCORES_PATH)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Whether a score is good enough to enter the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 &&
+        (self.entries.len() < MAX_ENTRIES || self.entries.last().map_or(true, |e| score > e.score))
+    }
+
+    pub fn insert(&mut self, initials: &str, score: u32, seed: u32) {
+        let date = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        self.entries.push(Entry {
+            initials: initials.to_string(),
+            score: score,
+            date: date,
+            seed: seed,
+        });
+
+        self.normalize();
+    }
+
+    fn normalize(&mut self) {
+        // Older entries win ties, as they got there first.
+        self.entries.sort_by(|a, b| b.score.cmp(&a.score).then(a.date.cmp(&b.date)));
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+/// Lists the high-score table.
+pub struct HighScoreView {
+    title: Sprite,
+    lines: Vec<Sprite>,
+    hint: Sprite,
+}
+
+impl HighScoreView {
+    pub fn new(phi: &mut Phi) -> HighScoreView {
+        let table = HighScores::load();
+
+        let mut lines: Vec<Sprite> = table.entries().iter().enumerate()
+            .filter_map(|(i, entry)| {
+                let text = format!("{:2}. {}  {:08}  {}",
+                                   i + 1, entry.initials, entry.score, format_date(entry.date));
+                phi.ttf_str_sprite(&text, FONT_PATH, 24, Color::RGB(220, 220, 220))
+            })
+            .collect();
+
+        if lines.is_empty() {
+            lines.extend(phi.ttf_str_sprite("No scores yet", FONT_PATH, 24, Color::RGB(160, 160, 160)));
+        }
+
+        HighScoreView {
+            title: phi.ttf_str_sprite("High Scores", FONT_PATH, 40, Color::RGB(255, 255, 0)).unwrap(),
+            lines: lines,
+            hint: phi.ttf_str_sprite("Press space to go back", FONT_PATH, 20, Color::RGB(160, 160, 160)).unwrap(),
+        }
+    }
+}
+
+impl View for HighScoreView {
+    fn render(&mut self, phi: &mut Phi, _: f64) -> ViewAction {
+        if phi.events.now.quit {
+            return ViewAction::Quit;
+        }
+
+        if phi.events.now.key_space == Some(true) || phi.events.now.key_escape == Some(true) {
+            return ViewAction::ChangeView(Box::new(::views::main_menu::MainMenuView::new(phi)));
+        }
+
+        phi.renderer.set_draw_color(Color::RGB(0, 0, 0));
+        phi.renderer.clear();
+
+        let (win_w, win_h) = phi.output_size();
+
+        let (w, h) = self.title.size();
+        phi.renderer.copy_sprite(&self.title, Rectangle {
+            x: (win_w - w) / 2.0,
+            y: 32.0,
+            w: w,
+            h: h,
+        });
+
+        for (i, line) in self.lines.iter().enumerate() {
+            let (w, h) = line.size();
+            phi.renderer.copy_sprite(line, Rectangle {
+                x: (win_w - w) / 2.0,
+                y: 112.0 + 36.0 * i as f64,
+                w: w,
+                h: h,
+            });
+        }
+
+        let (w, h) = self.hint.size();
+        phi.renderer.copy_sprite(&self.hint, Rectangle {
+            x: (win_w - w) / 2.0,
+            y: win_h - h - 24.0,
+            w: w,
+            h: h,
+        });
+
+        ViewAction::None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(source: &[u8]) -> HighScores {
+        HighScores::read(source)
+    }
+
+    #[test]
+    fn parses_entries() {
+        assert_eq!(Entry::parse("ABC 12345 1500000000 42"), Some(Entry {
+            initials: "ABC".to_string(),
+            score: 12345,
+            date: 1_500_000_000,
+            seed: 42,
+        }));
+
+        assert_eq!(Entry::parse("  XYZ\t0   0 0  ").map(|e| e.initials), Some("XYZ".to_string()));
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        for line in &["",
+                      "ABC",
+                      "ABC 100 0",
+                      "ABC 100 0 1 extra",
+                      "abc 100 0 1",
+                      "AB 100 0 1",
+                      "ABCD 100 0 1",
+                      "A1C 100 0 1",
+                      "ABC -100 0 1",
+                      "ABC 1.5 0 1",
+                      "ABC 99999999999 0 1",
+                      "ABC 100 yesterday 1"] {
+            assert_eq!(Entry::parse(line), None, "{:?} should not parse", line);
+        }
+    }
+
+    #[test]
+    fn skips_damaged_lines() {
+        let table = read(b"AAA 10 0 1\n\
+                           garbage\n\
+                           BBB 30 0 2 3\n\
+                           \xff\xfe 20 0 3\n\
+                           CCC 20 0 4\n\
+                           \n\
+                           DDD 40 0 5");
+
+        let initials: Vec<&str> = table.entries().iter().map(|e| &e.initials[..]).collect();
+        assert_eq!(initials, vec!["DDD", "CCC", "AAA"]);
+    }
+
+    #[test]
+    fn keeps_the_best_entries_in_order() {
+        let mut source = String::new();
+        for i in 0..(MAX_ENTRIES as u32 + 5) {
+            // Ties are broken by date, older entries first.
+            source.push_str(&format!("AAA {} {} {}\n", (i / 2) * 10, 100 - i, i));
+        }
+
+        let table = read(source.as_bytes());
+        assert_eq!(table.entries().len(), MAX_ENTRIES);
+
+        for pair in table.entries().windows(2) {
+            assert!(pair[0].score > pair[1].score ||
+                    (pair[0].score == pair[1].score && pair[0].date <= pair[1].date));
+        }
+
+        assert_eq!(table.entries()[0].score, (MAX_ENTRIES as u32 + 4) / 2 * 10);
+    }
+
+    #[test]
+    fn qualifies_only_scores_beating_a_full_table() {
+        assert!(!read(b"").qualifies(0));
+        assert!(read(b"").qualifies(1));
+
+        let mut source = String::new();
+        for _ in 0..MAX_ENTRIES {
+            source.push_str("AAA 100 0 0\n");
+        }
+
+        let table = read(source.as_bytes());
+        assert!(!table.qualifies(100));
+        assert!(table.qualifies(101));
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_500_000_000), "2017-07-14");
+    }
+}
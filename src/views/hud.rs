@@ -0,0 +1,183 @@
+use phi::Phi;
+use phi::data::Rectangle;
+use phi::gfx::{CopySprite, Sprite};
+use phi::tween::{Easing, Tween};
+use sdl2::pixels::Color;
+use views::powerups::{PowerUpKind, KINDS};
+use views::score::Score;
+
+const FONT_PATH: &'static str = "assets/belligerent.ttf";
+const MARGIN: f64 = 12.0;
+/// By how much a counter grows when it changes, before going back to its
+/// normal size over the given time, in seconds.
+const POP_SCALE: f64 = 1.4;
+const POP_DURATION: f64 = 0.5;
+
+/// A line of text which is only rendered again when its content changes, as
+/// creating a texture from a font every frame is expensive.
+struct CachedText {
+    text: String,
+    sprite: Option<Sprite>,
+    size: u16,
+    color: Color,
+}
+
+impl CachedText {
+    fn new(size: u16, color: Color) -> CachedText {
+        CachedText {
+            text: String::new(),
+            sprite: None,
+            size: size,
+            color: color,
+        }
+    }
+
+    fn sprite(&mut self, phi: &mut Phi, text: String) -> Option<&Sprite> {
+        if self.sprite.is_none() || self.text != text {
+            self.sprite = phi.ttf_str_sprite(&text, FONT_PATH, self.size, self.color);
+            self.text = text;
+        }
+
+        self.sprite.as_ref()
+    }
+}
+
+/// The overlay showing the score, the combo multiplier and the lives left.
+pub struct Hud {
+    score: CachedText,
+    multiplier: CachedText,
+    lives: CachedText,
+    // One label per kind of power-up, in the order of `KINDS`.
+    powerups: Vec<CachedText>,
+    // The scale of the counters, which pop when their value changes.
+    score_pop: Tween<f64>,
+    multiplier_pop: Tween<f64>,
+    last_points: u32,
+    last_multiplier: u32,
+}
+
+impl Hud {
+    pub fn new() -> Hud {
+        Hud {
+            score: CachedText::new(28, Color::RGB(255, 255, 255)),
+            multiplier: CachedText::new(22, Color::RGB(255, 200, 0)),
+            lives: CachedText::new(22, Color::RGB(220, 220, 220)),
+            powerups: KINDS.iter().map(|kind| CachedText::new(16, kind.color())).collect(),
+            score_pop: Tween::at(1.0),
+            multiplier_pop: Tween::at(1.0),
+            last_points: 0,
+            last_multiplier: 1,
+        }
+    }
+
+    pub fn render(&mut self, phi: &mut Phi, score: &Score, lives: u32, elapsed: f64) {
+        let (win_w, _) = phi.output_size();
+
+        if score.points() != self.last_points {
+            self.last_points = score.points();
+            self.score_pop = Tween::new(POP_SCALE, 1.0, POP_DURATION, Easing::ElasticOut);
+        }
+
+        // Only pop when the combo grows, not when it is lost.
+        if score.multiplier() > self.last_multiplier {
+            self.multiplier_pop = Tween::new(POP_SCALE, 1.0, POP_DURATION, Easing::BounceOut);
+        }
+        self.last_multiplier = score.multiplier();
+
+        self.score_pop.update(elapsed);
+        self.multiplier_pop.update(elapsed);
+
+        if let Some(sprite) = self.score.sprite(phi, format!("{:08}", score.points())).cloned() {
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(&sprite, Rectangle {
+                x: MARGIN,
+                y: MARGIN,
+                w: w,
+                h: h,
+            }.scale(self.score_pop.value()));
+        }
+
+        if score.multiplier() > 1 {
+            if let Some(sprite) = self.multiplier.sprite(phi, format!("x{}", score.multiplier())).cloned() {
+                let (w, h) = sprite.size();
+                phi.renderer.copy_sprite(&sprite, Rectangle {
+                    x: MARGIN,
+                    y: MARGIN + 32.0,
+                    w: w,
+                    h: h,
+                }.scale(self.multiplier_pop.value()));
+
+                // A bar under the multiplier shows how long the combo lasts.
+                phi.renderer.set_draw_color(Color::RGB(255, 200, 0));
+                let _ = phi.renderer.fill_rect(Rectangle {
+                    x: MARGIN,
+                    y: MARGIN + 32.0 + h,
+                    w: 60.0 * score.combo_left(),
+                    h: 3.0,
+                }.to_sdl());
+            }
+        }
+
+        if let Some(sprite) = self.lives.sprite(phi, format!("Lives {}", lives)).cloned() {
+            let (w, h) = sprite.size();
+            phi.renderer.copy_sprite(&sprite, Rectangle {
+                x: win_w - w - MARGIN,
+                y: MARGIN,
+                w: w,
+                h: h,
+            });
+        }
+    }
+
+    /// Lists the power-ups in effect under the lives, each with a bar
+    /// showing how long it lasts.
+    pub fn render_powerups(&mut self, phi: &mut Phi, active: &[(PowerUpKind, f64)]) {
+        let (win_w, _) = phi.output_size();
+        let mut y = MARGIN + 36.0;
+
+        for &(kind, left) in active {
+            let label = &mut self.powerups[kind as usize];
+
+            if let Some(sprite) = label.sprite(phi, kind.label().to_string()).cloned() {
+                let (w, h) = sprite.size();
+                phi.renderer.copy_sprite(&sprite, Rectangle {
+                    x: win_w - w - MARGIN,
+                    y: y,
+                    w: w,
+                    h: h,
+                });
+
+                phi.renderer.set_draw_color(kind.color());
+                let _ = phi.renderer.fill_rect(Rectangle {
+                    x: win_w - MARGIN - 60.0 * left,
+                    y: y + h,
+                    w: 60.0 * left,
+                    h: 3.0,
+                }.to_sdl());
+
+                y += h + 8.0;
+            }
+        }
+    }
+
+    /// Shows the health left to the boss as a bar along the bottom of the
+    /// screen.
+    pub fn render_boss_health(&mut self, phi: &mut Phi, health: f64) {
+        let (win_w, win_h) = phi.output_size();
+        let bar = Rectangle {
+            x: MARGIN * 4.0,
+            y: win_h - MARGIN - 10.0,
+            w: win_w - MARGIN * 8.0,
+            h: 10.0,
+        };
+
+        phi.renderer.set_draw_color(Color::RGB(80, 20, 20));
+        let _ = phi.renderer.fill_rect(bar.to_sdl());
+
+        phi.renderer.set_draw_color(Color::RGB(230, 40, 40));
+        let _ = phi.renderer.fill_rect(Rectangle {
+            w: bar.w * health.max(0.0).min(1.0),
+            ..bar
+        }.to_sdl());
+    }
+}
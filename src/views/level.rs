@@ -0,0 +1,638 @@
+//! Levels are plain text files listing timed events, one per line:
+//!
+//! ```text
+//! # Comments start with a hash.
+//! 0.0   asteroids rate=0.4 ramp=0.01 max=8
+//! 2.0   enemy flyer at=0.25 count=4 spacing=0.5
+//! 6.5   asteroid large at=0.5
+//! 10.0  background speed=2.0
+//! 20.0  scenery nebula
+//! 30.0  boss
+//! ```
+//!
+//! The first column is the time, in seconds since the start of the level, at
+//! which the event happens. Events must be listed in chronological order.
+//! Positions given through `at` are fractions of the height of the screen.
+//!
+//! Sceneries are named after files of `SCENERY_DIR`, which list the layers of
+//! a parallax background, furthest first:
+//!
+//! ```text
+//! layer assets/starBG.png speed=15 tint=ffaac8ff
+//! layer assets/starMG.png speed=30 drift=-6 tiling=native
+//! layer assets/starFG.png speed=100 depth=front
+//! ```
+//!
+//! Layers scroll left by `speed` and down by `drift` pixels per second. Their
+//! `tint` is given as RRGGBBAA, they are either scaled to `fit` the height of
+//! the screen or repeated at their `native` size, and drawn `behind` the ships
+//! or in `front` of them.
+
+use sdl2::pixels::Color;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use views::asteroids::{AsteroidSize, SpawnConfig};
+use views::enemies::EnemyKind;
+use views::shared::{Depth, LayerConfig, Tiling};
+
+/// Where the sceneries named by levels are found.
+pub const SCENERY_DIR: &'static str = "assets/scenery";
+
+/// The most enemies or asteroids a single command may bring in.
+const MAX_COUNT: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Enemy { kind: EnemyKind, at: f64 },
+    Asteroid { size: AsteroidSize, at: f64 },
+    /// Replaces the configuration of the asteroid spawner.
+    Asteroids(SpawnConfig),
+    /// Sets how fast the background scrolls, relative to its normal speed.
+    /// Only the scenery is affected, not the time of the game.
+    BackgroundSpeed(f64),
+    /// Replaces the parallax layers of the background by one of the
+    /// sceneries of the level.
+    Scenery(usize),
+    /// Brings in the boss, holding the timeline until it is defeated.
+    Boss,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    // Sorted by time.
+    events: Vec<(f64, Event)>,
+    // Along with their names, so that each is only loaded once.
+    sceneries: Vec<(String, Vec<LayerConfig>)>,
+}
+
+impl Level {
+    /// Loads a level, along with the sceneries it uses.
+    pub fn load(path: &str) -> Result<Level, String> {
+        let source = read(path)?;
+        Level::parse(&source, load_scenery).map_err(|e| format!("{}:{}", path, e))
+    }
+
+    /// Parses a level, getting the layers of the sceneries it names from
+    /// `load_scenery`.
+    pub fn parse<F>(source: &str, load_scenery: F) -> Result<Level, ParseError>
+        where F: Fn(&str) -> Result<Vec<LayerConfig>, String>
+    {
+        let mut events: Vec<(f64, Event)> = vec![];
+        let mut sceneries: Vec<(String, Vec<LayerConfig>)> = vec![];
+        let mut last_time = 0.0;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_no = index + 1;
+            let error = |message: String| ParseError { line: line_no, message: message };
+
+            let content = line.split('#').next().unwrap().trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            let mut words = content.split_whitespace();
+
+            let time_word = words.next().unwrap();
+            let time: f64 = time_word.parse()
+                .map_err(|_| error(format!("expected a time in seconds, found `{}`", time_word)))?;
+
+            if !time.is_finite() || time < 0.0 {
+                return Err(error(format!("time should be a positive number, found {}", time)));
+            }
+
+            if time < last_time {
+                return Err(error(format!("events must be in chronological order, but {} comes after {}",
+                                         time, last_time)));
+            }
+
+            last_time = time;
+
+            let command = words.next().ok_or_else(|| error("expected a command after the time".to_string()))?;
+            let mut args = Args::parse(words).map_err(&error)?;
+
+            match command {
+                "enemy" => {
+                    let kind = match args.positional("enemy kind").map_err(&error)? {
+                        "flyer" => EnemyKind::Flyer,
+                        "diver" => EnemyKind::Diver,
+                        "turret" => EnemyKind::Turret,
+                        other => return Err(error(format!("unknown enemy kind `{}`", other))),
+                    };
+
+                    let at = args.fraction("at", 0.5).map_err(&error)?;
+                    let count = args.count("count", 1).map_err(&error)?;
+                    let spacing = args.number("spacing", 0.0).map_err(&error)?;
+
+                    for i in 0..count {
+                        events.push((time + spacing * i as f64, Event::Enemy { kind: kind, at: at }));
+                    }
+                }
+
+                "asteroid" => {
+                    let size = match args.positional("asteroid size").map_err(&error)? {
+                        "small" => AsteroidSize::Small,
+                        "medium" => AsteroidSize::Medium,
+                        "large" => AsteroidSize::Large,
+                        other => return Err(error(format!("unknown asteroid size `{}`", other))),
+                    };
+
+                    let at = args.fraction("at", 0.5).map_err(&error)?;
+                    events.push((time, Event::Asteroid { size: size, at: at }));
+                }
+
+                "asteroids" => {
+                    let default = SpawnConfig::default();
+                    let config = SpawnConfig {
+                        rate: args.number("rate", default.rate).map_err(&error)?,
+                        rate_ramp: args.number("ramp", default.rate_ramp).map_err(&error)?,
+                        max_rate: args.number("max_rate", default.max_rate).map_err(&error)?,
+                        burst_chance: args.fraction("burst", default.burst_chance).map_err(&error)?,
+                        burst_size: args.count("burst_size", default.burst_size).map_err(&error)?,
+                        max_concurrent: args.count("max", default.max_concurrent).map_err(&error)?,
+                        size_weights: default.size_weights,
+                    };
+
+                    events.push((time, Event::Asteroids(config)));
+                }
+
+                "background" => {
+                    let speed = args.number("speed", 1.0).map_err(&error)?;
+                    events.push((time, Event::BackgroundSpeed(speed)));
+                }
+
+                "scenery" => {
+                    let name = args.positional("scenery name").map_err(&error)?;
+
+                    let index = match sceneries.iter().position(|&(ref n, _)| n == name) {
+                        Some(index) => index,
+                        None => {
+                            let layers = load_scenery(name)
+                                .map_err(|e| error(format!("cannot load the scenery `{}`: {}", name, e)))?;
+                            sceneries.push((name.to_string(), layers));
+                            sceneries.len() - 1
+                        }
+                    };
+
+                    events.push((time, Event::Scenery(index)));
+                }
+
+                "boss" => events.push((time, Event::Boss)),
+
+                other => return Err(error(format!("unknown command `{}`", other))),
+            }
+
+            args.finish().map_err(&error)?;
+        }
+
+        // Repeated enemies may overlap with the following events.
+        events.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        Ok(Level {
+            events: events,
+            sceneries: sceneries,
+        })
+    }
+
+    /// The layers of the sceneries which `Event::Scenery` refers to.
+    pub fn sceneries(&self) -> Vec<&[LayerConfig]> {
+        self.sceneries.iter().map(|&(_, ref layers)| &layers[..]).collect()
+    }
+}
+
+/// Loads the layers of a scenery from `SCENERY_DIR`.
+pub fn load_scenery(name: &str) -> Result<Vec<LayerConfig>, String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+        return Err(format!("`{}` is not a valid scenery name", name));
+    }
+
+    let path = format!("{}/{}.txt", SCENERY_DIR, name);
+    let source = read(&path)?;
+    parse_scenery(&source).map_err(|e| format!("{}:{}", path, e))
+}
+
+pub fn parse_scenery(source: &str) -> Result<Vec<LayerConfig>, ParseError> {
+    let mut layers = vec![];
+
+    for (index, line) in source.lines().enumerate() {
+        let line_no = index + 1;
+        let error = |message: String| ParseError { line: line_no, message: message };
+
+        let content = line.split('#').next().unwrap().trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        let mut words = content.split_whitespace();
+        match words.next().unwrap() {
+            "layer" => {}
+            other => return Err(error(format!("expected `layer`, found `{}`", other))),
+        }
+
+        let mut args = Args::parse(words).map_err(&error)?;
+        let path = args.positional("the path of an image").map_err(&error)?;
+
+        let tiling = match args.word("tiling", "fit").map_err(&error)? {
+            "fit" => Tiling::FitHeight,
+            "native" => Tiling::Native,
+            other => return Err(error(format!("unknown tiling `{}`", other))),
+        };
+
+        let depth = match args.word("depth", "behind").map_err(&error)? {
+            "behind" => Depth::Behind,
+            "front" => Depth::InFront,
+            other => return Err(error(format!("unknown depth `{}`", other))),
+        };
+
+        layers.push(LayerConfig {
+            path: path.to_string(),
+            velocity: (args.signed("speed", 0.0).map_err(&error)?,
+                       args.signed("drift", 0.0).map_err(&error)?),
+            tint: args.color("tint", Color::RGBA(255, 255, 255, 255)).map_err(&error)?,
+            tiling: tiling,
+            depth: depth,
+        });
+
+        args.finish().map_err(&error)?;
+    }
+
+    if layers.is_empty() {
+        return Err(ParseError {
+            line: source.lines().count().max(1),
+            message: "a scenery needs at least one layer".to_string(),
+        });
+    }
+
+    Ok(layers)
+}
+
+fn read(path: &str) -> Result<String, String> {
+    let mut source = String::new();
+
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut source))
+        .map_err(|e| format!("{}: {}", path, e))?;
+
+    Ok(source)
+}
+
+/// The arguments following a command: positional words first, then
+/// `key=value` pairs.
+struct Args<'a> {
+    positional: Vec<&'a str>,
+    named: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Args<'a> {
+    fn parse<I: Iterator<Item = &'a str>>(words: I) -> Result<Args<'a>, String> {
+        let mut args = Args {
+            positional: vec![],
+            named: vec![],
+        };
+
+        for word in words {
+            match word.find('=') {
+                Some(i) => {
+                    let (key, value) = (&word[..i], &word[i + 1..]);
+                    if args.named.iter().any(|&(k, _)| k == key) {
+                        return Err(format!("`{}` is given twice", key));
+                    }
+                    args.named.push((key, value));
+                }
+                None if args.named.is_empty() => args.positional.push(word),
+                None => return Err(format!("unexpected `{}` after named arguments", word)),
+            }
+        }
+
+        // Positional arguments are consumed from the front.
+        args.positional.reverse();
+        Ok(args)
+    }
+
+    fn positional(&mut self, what: &str) -> Result<&'a str, String> {
+        self.positional.pop().ok_or_else(|| format!("expected {}", what))
+    }
+
+    /// Takes the value of a named argument out of the remaining ones.
+    fn take(&mut self, key: &str) -> Option<&'a str> {
+        self.named.iter()
+            .position(|&(k, _)| k == key)
+            .map(|i| self.named.remove(i).1)
+    }
+
+    fn word(&mut self, key: &str, default: &'a str) -> Result<&'a str, String> {
+        Ok(self.take(key).unwrap_or(default))
+    }
+
+    /// A finite number, which may be negative.
+    fn signed(&mut self, key: &str, default: f64) -> Result<f64, String> {
+        let value = match self.take(key) {
+            Some(value) => value,
+            None => return Ok(default),
+        };
+
+        let number: f64 = value.parse()
+            .map_err(|_| format!("`{}` should be a number, found `{}`", key, value))?;
+
+        if number.is_finite() {
+            Ok(number)
+        } else {
+            Err(format!("`{}` should be a finite number, found {}", key, number))
+        }
+    }
+
+    fn number(&mut self, key: &str, default: f64) -> Result<f64, String> {
+        let number = self.signed(key, default)?;
+
+        if number < 0.0 {
+            Err(format!("`{}` cannot be negative, found {}", key, number))
+        } else {
+            Ok(number)
+        }
+    }
+
+    /// A whole number, up to `MAX_COUNT`.
+    fn count(&mut self, key: &str, default: usize) -> Result<usize, String> {
+        let value = match self.take(key) {
+            Some(value) => value,
+            None => return Ok(default),
+        };
+
+        let count: usize = value.parse()
+            .map_err(|_| format!("`{}` should be a whole number, found `{}`", key, value))?;
+
+        if count > MAX_COUNT {
+            Err(format!("`{}` cannot be more than {}, found {}", key, MAX_COUNT, count))
+        } else {
+            Ok(count)
+        }
+    }
+
+    /// A color written as RRGGBBAA, in hexadecimal.
+    fn color(&mut self, key: &str, default: Color) -> Result<Color, String> {
+        let value = match self.take(key) {
+            Some(value) => value,
+            None => return Ok(default),
+        };
+
+        match u32::from_str_radix(value, 16) {
+            Ok(rgba) if value.len() == 8 && value.chars().all(|c| c.is_digit(16)) => {
+                let channel = |shift: u32| (rgba >> shift) as u8;
+                Ok(Color::RGBA(channel(24), channel(16), channel(8), channel(0)))
+            }
+            _ => Err(format!("`{}` should be a color written as RRGGBBAA, found `{}`", key, value)),
+        }
+    }
+
+    fn fraction(&mut self, key: &str, default: f64) -> Result<f64, String> {
+        let number = self.number(key, default)?;
+
+        if number > 1.0 {
+            Err(format!("`{}` should be between 0 and 1, found {}", key, number))
+        } else {
+            Ok(number)
+        }
+    }
+
+    /// Fails if some arguments were not used by the command.
+    fn finish(&self) -> Result<(), String> {
+        if let Some(word) = self.positional.last() {
+            return Err(format!("unexpected `{}`", word));
+        }
+
+        match self.named.first() {
+            Some(&(key, _)) => Err(format!("unknown argument `{}`", key)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Plays a level back, handing out its events as their time comes.
+pub struct Timeline {
+    level: Level,
+    time: f64,
+    next: usize,
+    held: bool,
+}
+
+impl Timeline {
+    pub fn new(level: Level) -> Timeline {
+        Timeline {
+            level: level,
+            time: 0.0,
+            next: 0,
+            held: false,
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.held = false;
+    }
+
+    /// Advances time and returns the events which happen this frame. Time
+    /// stops after a boss shows up, until `resume` is called.
+    pub fn update(&mut self, dt: f64) -> Vec<Event> {
+        let mut events = vec![];
+        if self.held {
+            return events;
+        }
+
+        self.time += dt;
+
+        while let Some(&(time, event)) = self.level.events.get(self.next) {
+            if time > self.time {
+                break;
+            }
+
+            self.next += 1;
+            events.push(event);
+
+            // Events following a boss wait for it to be defeated.
+            if event == Event::Boss {
+                self.held = true;
+                break;
+            }
+        }
+
+        events
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(path: &str) -> LayerConfig {
+        LayerConfig {
+            path: path.to_string(),
+            velocity: (0.0, 0.0),
+            tint: Color::RGBA(255, 255, 255, 255),
+            tiling: Tiling::FitHeight,
+            depth: Depth::Behind,
+        }
+    }
+
+    fn parse(source: &str) -> Result<Level, ParseError> {
+        Level::parse(source, |name| match name {
+            "stars" | "nebula" => Ok(vec![layer(name)]),
+            _ => Err("no such scenery".to_string()),
+        })
+    }
+
+    fn error(source: &str) -> ParseError {
+        parse(source).err().expect("the level should not parse")
+    }
+
+    #[test]
+    fn parses_events_in_order() {
+        let level = parse("# The start.\n\
+                                  0.0 enemy flyer at=0.25 count=3 spacing=1.5\n\
+                                  \n\
+                                  2.0 asteroid large   # In between.\n\
+                                  4.0 boss\n").unwrap();
+
+        let times: Vec<f64> = level.events.iter().map(|&(time, _)| time).collect();
+        assert_eq!(times, vec![0.0, 1.5, 2.0, 3.0, 4.0]);
+        assert_eq!(level.events[1].1, Event::Enemy { kind: EnemyKind::Flyer, at: 0.25 });
+        assert_eq!(level.events[2].1, Event::Asteroid { size: AsteroidSize::Large, at: 0.5 });
+        assert_eq!(level.events[4].1, Event::Boss);
+    }
+
+    #[test]
+    fn loads_the_levels_of_the_game() {
+        let level = Level::load("assets/levels/level1.txt").unwrap();
+        assert!(!level.sceneries().is_empty());
+
+        for name in &["stars", "nebula"] {
+            load_scenery(name).unwrap();
+        }
+    }
+
+    #[test]
+    fn loads_each_scenery_once() {
+        let level = parse("0.0 scenery nebula\n\
+                           1.0 scenery stars\n\
+                           2.0 scenery nebula").unwrap();
+
+        let events: Vec<Event> = level.events.iter().map(|&(_, event)| event).collect();
+        assert_eq!(events, vec![Event::Scenery(0), Event::Scenery(1), Event::Scenery(0)]);
+
+        let sceneries = level.sceneries();
+        assert_eq!(sceneries.len(), 2);
+        assert_eq!(sceneries[0][0].path, "nebula");
+        assert_eq!(sceneries[1][0].path, "stars");
+    }
+
+    #[test]
+    fn parses_sceneries() {
+        let layers = parse_scenery("# Far away.\n\
+                                    layer far.png speed=10 drift=-2.5\n\
+                                    \n\
+                                    layer near.png tint=ff804020 tiling=native depth=front\n").unwrap();
+
+        assert_eq!(layers, vec![
+            LayerConfig {
+                velocity: (10.0, -2.5),
+                ..layer("far.png")
+            },
+            LayerConfig {
+                tint: Color::RGBA(255, 128, 64, 32),
+                tiling: Tiling::Native,
+                depth: Depth::InFront,
+                ..layer("near.png")
+            },
+        ]);
+    }
+
+    #[test]
+    fn rejects_malformed_sceneries() {
+        let line = |source: &str| parse_scenery(source).err().expect("the scenery should not parse").line;
+
+        assert_eq!(line("# Nothing.\n"), 1);
+        assert_eq!(line("layer a.png\nsprite b.png"), 2);
+        assert_eq!(line("layer a.png\nlayer"), 2);
+        assert_eq!(line("layer a.png speed=fast"), 1);
+        assert_eq!(line("layer a.png drift=inf"), 1);
+        assert_eq!(line("layer a.png tint=red"), 1);
+        assert_eq!(line("layer a.png tint=ffffff"), 1);
+        assert_eq!(line("layer a.png tint=+fffffff"), 1);
+        assert_eq!(line("layer a.png tiling=stretch"), 1);
+        assert_eq!(line("layer a.png depth=middle"), 1);
+        assert_eq!(line("layer a.png b.png"), 1);
+        assert_eq!(line("layer a.png\n\nlayer b.png size=2"), 3);
+
+        assert!(load_scenery("../levels/level1").is_err());
+        assert!(load_scenery("").is_err());
+    }
+
+    #[test]
+    fn rejects_times_which_are_not_finite() {
+        for time in &["NaN", "inf", "-inf", "-1.0", "soon"] {
+            let source = format!("0.0 boss\n{} boss", time);
+            assert_eq!(error(&source).line, 2, "{} should be rejected", time);
+        }
+    }
+
+    #[test]
+    fn rejects_numbers_which_are_not_finite() {
+        for value in &["NaN", "inf", "-1.0", "fast"] {
+            let source = format!("# Speed.\n\n1.0 background speed={}", value);
+            assert_eq!(error(&source).line, 3, "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn rejects_counts_which_are_not_whole() {
+        for count in &["NaN", "inf", "1e12", "2.5", "-1", "101"] {
+            let source = format!("0.0 enemy flyer count={}", count);
+            assert_eq!(error(&source).line, 1, "{} should be rejected", count);
+
+            let source = format!("0.0 boss\n0.0 asteroids max={}", count);
+            assert_eq!(error(&source).line, 2, "{} should be rejected", count);
+        }
+    }
+
+    #[test]
+    fn rejects_fractions_out_of_range() {
+        assert_eq!(error("0.0 asteroid small at=1.5").line, 1);
+        assert_eq!(error("0.0 boss\n0.0 asteroids burst=2").line, 2);
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        assert_eq!(error("1.0 boss\n0.5 boss").line, 2);
+        assert_eq!(error("0.0").line, 1);
+        assert_eq!(error("0.0 dance").line, 1);
+        assert_eq!(error("0.0 enemy").line, 1);
+        assert_eq!(error("0.0 enemy blob").line, 1);
+        assert_eq!(error("0.0 enemy flyer at=0.5 at=0.5").line, 1);
+        assert_eq!(error("0.0 enemy flyer colour=red").line, 1);
+        assert_eq!(error("0.0 boss now").line, 1);
+        assert_eq!(error("0.0 scenery jungle").line, 1);
+    }
+
+    #[test]
+    fn holds_the_timeline_on_bosses() {
+        let mut timeline = Timeline::new(parse("1.0 boss\n2.0 asteroid small").unwrap());
+
+        assert_eq!(timeline.update(0.5), vec![]);
+        assert_eq!(timeline.update(5.0), vec![Event::Boss]);
+        assert_eq!(timeline.update(5.0), vec![]);
+
+        timeline.resume();
+        assert_eq!(timeline.update(0.0), vec![Event::Asteroid { size: AsteroidSize::Small, at: 0.5 }]);
+    }
+}
@@ -1,92 +1,261 @@
 use phi::{Phi, View, ViewAction};
-use phi::data::Rectangle;
+use phi::audio::Sound;
+use phi::data::{Rectangle, Vec2};
+use phi::tween::{Easing, Sequence, Tween};
 use sdl2::pixels::Color;
-use phi::gfx::{Sprite, CopySprite};
+use sdl2::render::BlendMode;
+use phi::gfx::Sprite;
+use std::cell::Cell;
+use std::rc::Rc;
+
+const THEME_PATH: &'static str = "assets/music/theme.wav";
+const SELECT_SOUND_PATH: &'static str = "assets/sfx/select.wav";
+
+/// How long, in seconds, it takes for an item to be highlighted and to go
+/// back to normal.
+const HIGHLIGHT_IN: f64 = 0.4;
+const HIGHLIGHT_OUT: f64 = 0.15;
+/// Items slide in from the left one after the other when the menu opens.
+const INTRO_DURATION: f64 = 0.6;
+const INTRO_STAGGER: f64 = 0.1;
+/// How long the cursor takes to slide from one item to the next.
+const CURSOR_SLIDE: f64 = 0.2;
+/// How long the menu takes to fade out once an item was chosen.
+const OUTRO_DURATION: f64 = 0.25;
+/// How long an error takes to fade in.
+const ERROR_FADE: f64 = 0.5;
+const FONT_PATH: &'static str = "assets/belligerent.ttf";
 
 pub struct MainMenuView {
     actions: Vec<Action>,
     selected: i8,
+    // The box behind the selected item, which slides and resizes to fit the
+    // next one.
+    cursor: Tween<Rectangle>,
+    select_sound: Option<Sound>,
+    // The fade out played once an item was chosen, which hands the item over
+    // to `ready` when it is done.
+    outro: Option<Sequence<f64>>,
+    ready: Rc<Cell<Option<usize>>>,
+    // Why the previous view could not be opened, if it failed.
+    error: Option<Sprite>,
+    error_fade: Tween<f64>,
 }
 
 struct Action {
-    func: Box<Fn(&mut Phi) -> ViewAction>,
+    func: Box<dyn Fn(&mut Phi) -> ViewAction>,
     idle_sprite: Sprite,
     hover_sprite: Sprite,
+    // From 0 when idle to 1 when hovered.
+    highlight: Tween<f64>,
+    // The horizontal offset of the item, as it slides in.
+    intro: Sequence<f64>,
 }
 
 impl Action {
-    fn new(phi: &mut Phi, label: &'static str, func: Box<Fn(&mut Phi) -> ViewAction>) -> Action {
+    fn new(phi: &mut Phi, label: &'static str, func: Box<dyn Fn(&mut Phi) -> ViewAction>) -> Action {
         Action {
             func: func,
-            idle_sprite: phi.ttf_str_sprite(label, "assets/belligerent.ttf", 32, Color::RGB(220, 220, 220)).unwrap(),
-            hover_sprite: phi.ttf_str_sprite(label, "assets/belligerent.ttf", 38, Color::RGB(255, 255, 0)).unwrap(),
+            idle_sprite: phi.ttf_str_sprite(label, FONT_PATH, 32, Color::RGB(220, 220, 220)).unwrap(),
+            hover_sprite: phi.ttf_str_sprite(label, FONT_PATH, 38, Color::RGB(255, 255, 0)).unwrap(),
+            highlight: Tween::at(0.0),
+            intro: Sequence::new(0.0),
         }
     }
+
+    /// Crossfades from the idle sprite to the larger hover sprite as the
+    /// item gets highlighted. Both sprites grow together, each keeping its
+    /// own proportions, and may briefly grow past the hover size when the
+    /// highlight overshoots.
+    fn render(&self, phi: &mut Phi, center: Vec2) {
+        let highlight = self.highlight.value();
+        let (idle_w, idle_h) = self.idle_sprite.size();
+        let (hover_w, hover_h) = self.hover_sprite.size();
+
+        // The size of the text, relative to its idle size.
+        let growth = hover_h / idle_h;
+        let scale = 1.0 + (growth - 1.0) * highlight;
+
+        let idle = Rectangle::from_center(center, idle_w, idle_h).scale(scale);
+        let hover = Rectangle::from_center(center, hover_w, hover_h).scale(scale / growth);
+        let alpha = |amount: f64| Color::RGBA(255, 255, 255, (amount.max(0.0).min(1.0) * 255.0) as u8);
+
+        self.idle_sprite.render_tinted(&mut phi.renderer, idle, alpha(1.0 - highlight));
+        self.hover_sprite.render_tinted(&mut phi.renderer, hover, alpha(highlight));
+    }
 }
 
 impl MainMenuView {
     pub fn new(phi: &mut Phi) -> MainMenuView {
-        MainMenuView {
+        phi.audio.play_music(THEME_PATH, 1.0);
+
+        let mut menu = MainMenuView {
             actions: vec![
                 Action::new(phi, "New Game", Box::new(|phi| {
-                    ViewAction::ChangeView(Box::new(::views::game::ShipView::new(phi)))
+                    let game = ::views::game::ShipView::new(phi);
+                    ::views::game::ShipView::start(phi, game)
+                })),
+                Action::new(phi, "High Scores", Box::new(|phi| {
+                    ViewAction::ChangeView(Box::new(::views::highscores::HighScoreView::new(phi)))
                 })),
                 Action::new(phi, "Quit", Box::new(|_| {
                     ViewAction::Quit
                 })),
             ],
             selected: 0,
+            cursor: Tween::at(Rectangle { x: 0.0, y: 0.0, w: 0.0, h: 0.0 }),
+            select_sound: phi.audio.load_sound(SELECT_SOUND_PATH),
+            outro: None,
+            ready: Rc::new(Cell::new(None)),
+            error: None,
+            error_fade: Tween::at(0.0),
+        };
+
+        let (win_w, _) = phi.output_size();
+        for (i, action) in menu.actions.iter_mut().enumerate() {
+            action.intro = Sequence::new(-win_w)
+                .wait(INTRO_STAGGER * i as f64)
+                .to(0.0, INTRO_DURATION, Easing::CubicOut);
         }
+
+        menu.actions[0].highlight = Tween::new(0.0, 1.0, HIGHLIGHT_IN, Easing::ElasticOut);
+        menu.cursor = Tween::at(menu.cursor_around(phi, 0));
+        menu
+    }
+
+    /// The menu, showing why the game could not be started.
+    pub fn with_error(phi: &mut Phi, message: &str) -> MainMenuView {
+        let mut menu = MainMenuView::new(phi);
+        menu.error = phi.ttf_str_sprite(message, FONT_PATH, 16, Color::RGB(255, 80, 80));
+        menu.error_fade = Tween::new(0.0, 1.0, ERROR_FADE, Easing::QuadInOut);
+        menu
+    }
+
+    /// Where the item at `index` is drawn once it slid in.
+    fn item_center(&self, phi: &mut Phi, index: usize) -> Vec2 {
+        let (win_w, _) = phi.output_size();
+        let (_, h) = self.actions[index].idle_sprite.size();
+        Vec2::new(win_w / 2.0, 32.0 + 48.0 * index as f64 + h / 2.0)
+    }
+
+    /// The box around the item at `index`, fitting its highlighted text.
+    fn cursor_around(&self, phi: &mut Phi, index: usize) -> Rectangle {
+        let (w, h) = self.actions[index].hover_sprite.size();
+        Rectangle::from_center(self.item_center(phi, index), w + 32.0, h + 4.0)
+    }
+
+    fn select(&mut self, phi: &mut Phi, selected: i8) {
+        if let Some(sound) = self.select_sound {
+            phi.audio.play(sound);
+        }
+
+        self.actions[self.selected as usize].highlight.retarget(0.0, HIGHLIGHT_OUT, Easing::QuadOut);
+        self.actions[selected as usize].highlight.retarget(1.0, HIGHLIGHT_IN, Easing::ElasticOut);
+        let cursor = self.cursor_around(phi, selected as usize);
+        self.cursor.retarget(cursor, CURSOR_SLIDE, Easing::CubicInOut);
+        self.selected = selected;
     }
 }
 
 impl View for MainMenuView {
-    fn render(&mut self, phi: &mut Phi, elapsed: f64) -> ViewAction {
+    fn render(&mut self, phi: &mut Phi, _: f64) -> ViewAction {
+        // Menus are animated in real time, whatever happens to the game.
+        let elapsed = phi.clock.real_elapsed();
+
         if phi.events.now.quit || phi.events.now.key_escape == Some(true) {
             return ViewAction::Quit;
         }
 
-        if phi.events.now.key_space == Some(true) {
-            return (self.actions[self.selected as usize].func)(phi);
+        if let Some(ref mut outro) = self.outro {
+            outro.update(elapsed);
         }
 
-        if phi.events.now.key_up == Some(true) {
-            self.selected -= 1;
-            if self.selected < 0 {
-                self.selected = self.actions.len() as i8 - 1;
-            }
+        if let Some(index) = self.ready.take() {
+            return (self.actions[index].func)(phi);
         }
 
-        if phi.events.now.key_down == Some(true) {
-            self.selected += 1;
-            if self.selected >= self.actions.len() as i8 {
-                self.selected = 0;
+        if self.outro.is_none() {
+            if phi.events.now.key_space == Some(true) {
+                let (ready, index) = (self.ready.clone(), self.selected as usize);
+                self.outro = Some(Sequence::new(0.0)
+                    .to(1.0, OUTRO_DURATION, Easing::QuadIn)
+                    .call(move || ready.set(Some(index))));
+
+                // The items fly off to the right as the menu fades out.
+                let (win_w, _) = phi.output_size();
+                for action in &mut self.actions {
+                    action.intro = Sequence::new(action.intro.value())
+                        .to(win_w, OUTRO_DURATION, Easing::CubicIn);
+                }
+            }
+
+            if phi.events.now.key_up == Some(true) {
+                let selected = if self.selected == 0 {
+                    self.actions.len() as i8 - 1
+                } else {
+                    self.selected - 1
+                };
+                self.select(phi, selected);
+            }
+
+            if phi.events.now.key_down == Some(true) {
+                let selected = if self.selected == self.actions.len() as i8 - 1 {
+                    0
+                } else {
+                    self.selected + 1
+                };
+                self.select(phi, selected);
             }
         }
 
+        for action in &mut self.actions {
+            action.highlight.update(elapsed);
+            action.intro.update(elapsed);
+        }
+        self.cursor.update(elapsed);
+        self.error_fade.update(elapsed);
+
         phi.renderer.set_draw_color(Color::RGB(0,0,0));
         phi.renderer.clear();
 
         let (win_w, win_h) = phi.output_size();
 
-        for (i, action) in self.actions.iter().enumerate() {
-            if self.selected as usize == i {
-                let (w, h) = action.idle_sprite.size();
-                phi.renderer.copy_sprite(&action.hover_sprite, Rectangle {
-                    x: (win_w - w) / 2.0,
-                    y: 32.0 + 48.0 * i as f64,
-                    w: w,
-                    h: h,
-                });
-            } else {
-                let (w, h) = action.idle_sprite.size();
-                phi.renderer.copy_sprite(&action.idle_sprite, Rectangle {
-                    x: (win_w - w) / 2.0,
-                    y: 32.0 + 48.0 * i as f64,
-                    w: w,
-                    h: h,
-                });
-            }
+        // The cursor only shows up once every item slid in, and is gone as
+        // soon as they leave.
+        if self.outro.is_none() && self.actions.iter().all(|action| action.intro.is_done()) {
+            phi.renderer.set_blend_mode(BlendMode::Blend);
+            phi.renderer.set_draw_color(Color::RGBA(255, 255, 0, 40));
+            let _ = phi.renderer.fill_rect(self.cursor.value().to_sdl());
+            phi.renderer.set_blend_mode(BlendMode::None);
+        }
+
+        for i in 0..self.actions.len() {
+            let center = self.item_center(phi, i);
+            let action = &self.actions[i];
+            action.render(phi, Vec2::new(center.x + action.intro.value(), center.y));
+        }
+
+        if let Some(ref error) = self.error {
+            let (w, h) = error.size();
+            let alpha = (self.error_fade.value() * 255.0) as u8;
+            error.render_tinted(&mut phi.renderer, Rectangle {
+                x: (win_w - w) / 2.0,
+                y: win_h - h - 16.0,
+                w: w,
+                h: h,
+            }, Color::RGBA(255, 255, 255, alpha));
+        }
+
+        if let Some(ref outro) = self.outro {
+            phi.renderer.set_blend_mode(BlendMode::Blend);
+            phi.renderer.set_draw_color(Color::RGBA(0, 0, 0, (outro.value() * 255.0) as u8));
+            let _ = phi.renderer.fill_rect(Rectangle {
+                x: 0.0,
+                y: 0.0,
+                w: win_w,
+                h: win_h,
+            }.to_sdl());
+            phi.renderer.set_blend_mode(BlendMode::None);
         }
 
         ViewAction::None
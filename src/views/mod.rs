@@ -1,3 +1,15 @@
+pub mod asteroids;
+pub mod boss;
+pub mod enemies;
 pub mod game;
+pub mod game_over;
+pub mod highscores;
+pub mod hud;
+pub mod level;
 pub mod main_menu;
+pub mod patterns;
+pub mod pause;
+pub mod powerups;
+pub mod score;
 pub mod shared;
+pub mod weapons;
@@ -0,0 +1,250 @@
+use phi::Phi;
+use phi::data::{Rectangle, Vec2};
+use phi::ecs::{Body, Collider, Drawable, Entity, Offscreen, Velocity, World};
+use sdl2::pixels::Color;
+use std::f64::consts::PI;
+
+const BULLET_SIDE: f64 = 6.0;
+/// The collision layer of the bullets fired by enemies.
+pub const ENEMY_BULLET_LAYER: u32 = 16;
+/// The shortest time between two volleys. Any shorter and a single frame
+/// could fire an endless amount of them.
+const MIN_INTERVAL: f64 = 0.05;
+
+/// How the bullets of a single volley are laid out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    /// A single bullet fired at the target.
+    Aimed,
+    /// `count` bullets fanned over `arc` radians, centered on the target.
+    Spread { count: usize, arc: f64 },
+    /// `count` bullets evenly spaced around the emitter.
+    Ring { count: usize },
+    /// `arms` evenly spaced bullets whose direction rotates by
+    /// `rotation_speed` radians per second, regardless of the target.
+    Spiral { arms: usize, rotation_speed: f64 },
+}
+
+/// Describes a bullet pattern: what every volley looks like, how often it is
+/// fired and how the bullets then behave.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pattern {
+    pub shape: Shape,
+    /// The time, in seconds, between two volleys, no shorter than
+    /// `MIN_INTERVAL`.
+    pub interval: f64,
+    /// The initial speed of the bullets, in pixels per second.
+    pub speed: f64,
+    /// Added to the speed of the bullets every second, may be negative.
+    pub acceleration: f64,
+    /// The rate, in radians per second, at which bullets curve after being
+    /// fired.
+    pub turn: f64,
+}
+
+/// Every pattern known to the game, by name.
+pub const PATTERNS: &'static [(&'static str, Pattern)] = &[
+    ("aimed", Pattern {
+        shape: Shape::Aimed,
+        interval: 1.6,
+        speed: 220.0,
+        acceleration: 0.0,
+        turn: 0.0,
+    }),
+    ("spread", Pattern {
+        shape: Shape::Spread { count: 3, arc: 0.6 },
+        interval: 1.2,
+        speed: 160.0,
+        acceleration: 40.0,
+        turn: 0.0,
+    }),
+    ("ring", Pattern {
+        shape: Shape::Ring { count: 12 },
+        interval: 2.0,
+        speed: 120.0,
+        acceleration: 0.0,
+        turn: 0.0,
+    }),
+    ("spiral", Pattern {
+        shape: Shape::Spiral { arms: 3, rotation_speed: 2.0 },
+        interval: 0.15,
+        speed: 140.0,
+        acceleration: -20.0,
+        turn: 0.3,
+    }),
+];
+
+/// Looks up one of the `PATTERNS` by name.
+pub fn pattern(name: &str) -> Option<Pattern> {
+    PATTERNS.iter().find(|&&(n, _)| n == name).map(|&(_, pattern)| pattern)
+}
+
+/// Where enemies shoot at, if anywhere, as updated by the game every frame.
+pub struct Aim(pub Option<Vec2>);
+
+/// A bullet about to be fired by an enemy.
+#[derive(Clone, Copy)]
+pub struct EnemyBullet {
+    pos: (f64, f64),
+    // The direction of the bullet, in radians.
+    angle: f64,
+    speed: f64,
+    acceleration: f64,
+    turn: f64,
+}
+
+impl EnemyBullet {
+    pub fn spawn(self, world: &mut World) -> Entity {
+        let bullet = world.spawn();
+        let center = Vec2::new(self.pos.0, self.pos.1);
+
+        world.insert(bullet, Body(Rectangle::from_center(center, BULLET_SIDE, BULLET_SIDE)));
+        world.insert(bullet, Velocity(Vec2::new(self.angle.cos(), self.angle.sin()) * self.speed));
+        world.insert(bullet, Offscreen);
+        world.insert(bullet, Drawable::Rect {
+            color: Color::RGB(255, 70, 140),
+            border: None,
+        });
+        world.insert(bullet, Collider {
+            layers: ENEMY_BULLET_LAYER,
+            collides_with: 0,
+        });
+        world.insert(bullet, Thrust {
+            angle: self.angle,
+            speed: self.speed,
+            acceleration: self.acceleration,
+            turn: self.turn,
+        });
+        bullet
+    }
+}
+
+/// Changes the speed and the direction of an enemy bullet over time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Thrust {
+    // Kept apart from the velocity so that bullets which come to a halt keep
+    // their direction.
+    angle: f64,
+    speed: f64,
+    acceleration: f64,
+    turn: f64,
+}
+
+/// Speeds bullets up, slows them down and curves them, as their pattern
+/// requires.
+pub fn thrust(world: &mut World, _: &mut Phi, dt: f64) {
+    for entity in world.with::<Thrust>() {
+        let thrust = {
+            let thrust = world.get_mut::<Thrust>(entity).unwrap();
+            thrust.speed = (thrust.speed + thrust.acceleration * dt).max(0.0);
+            thrust.angle += thrust.turn * dt;
+            *thrust
+        };
+
+        // Bullets which slowed down to a halt would otherwise stay forever.
+        if thrust.speed == 0.0 && thrust.acceleration <= 0.0 {
+            world.despawn(entity);
+        }
+
+        world.insert(entity, Velocity(Vec2::new(thrust.angle.cos(), thrust.angle.sin()) * thrust.speed));
+    }
+}
+
+/// Fires the volleys of a pattern at regular intervals.
+#[derive(Clone, Copy)]
+pub struct Emitter {
+    pattern: Pattern,
+    // The time left before the next volley.
+    cooldown: f64,
+    // The current direction of spirals.
+    rotation: f64,
+}
+
+impl Emitter {
+    pub fn new(pattern: Pattern) -> Emitter {
+        let pattern = Pattern {
+            interval: pattern.interval.max(MIN_INTERVAL),
+            ..pattern
+        };
+
+        Emitter {
+            pattern: pattern,
+            cooldown: pattern.interval,
+            rotation: 0.0,
+        }
+    }
+
+    /// Advances time and returns the bullets fired from `origin` this frame,
+    /// aiming at `target` where the pattern requires it.
+    pub fn update(&mut self, dt: f64, origin: (f64, f64), target: (f64, f64)) -> Vec<EnemyBullet> {
+        if let Shape::Spiral { rotation_speed, .. } = self.pattern.shape {
+            self.rotation += rotation_speed * dt;
+        }
+
+        self.cooldown -= dt;
+        let mut bullets = vec![];
+
+        while self.cooldown <= 0.0 {
+            self.cooldown += self.pattern.interval;
+            bullets.extend(self.volley(origin, target));
+        }
+
+        bullets
+    }
+
+    fn volley(&self, origin: (f64, f64), target: (f64, f64)) -> Vec<EnemyBullet> {
+        let aim = (target.1 - origin.1).atan2(target.0 - origin.0);
+
+        let angles: Vec<f64> = match self.pattern.shape {
+            Shape::Aimed => vec![aim],
+            Shape::Spread { count, arc } => {
+                if count <= 1 {
+                    vec![aim]
+                } else {
+                    (0..count)
+                        .map(|i| aim - arc / 2.0 + arc * i as f64 / (count - 1) as f64)
+                        .collect()
+                }
+            }
+            Shape::Ring { count } => {
+                (0..count).map(|i| aim + 2.0 * PI * i as f64 / count as f64).collect()
+            }
+            Shape::Spiral { arms, .. } => {
+                (0..arms).map(|i| self.rotation + 2.0 * PI * i as f64 / arms as f64).collect()
+            }
+        };
+
+        angles.into_iter().map(|angle| {
+            EnemyBullet {
+                pos: origin,
+                angle: angle,
+                speed: self.pattern.speed,
+                acceleration: self.pattern.acceleration,
+                turn: self.pattern.turn,
+            }
+        }).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_the_interval() {
+        for &interval in &[0.0, -1.0, ::std::f64::NAN] {
+            let mut emitter = Emitter::new(Pattern {
+                shape: Shape::Aimed,
+                interval: interval,
+                speed: 100.0,
+                acceleration: 0.0,
+                turn: 0.0,
+            });
+
+            // Give or take a volley for rounding errors.
+            let fired = emitter.update(1.0, (0.0, 0.0), (100.0, 0.0)).len() as f64;
+            assert!((fired - 1.0 / MIN_INTERVAL).abs() <= 1.0, "fired {} volleys", fired);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use phi::Phi;
+use phi::audio::{Sound, Volumes};
+use phi::data::Rectangle;
+use phi::gfx::{CopySprite, Sprite};
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+const FONT_PATH: &'static str = "assets/belligerent.ttf";
+const SELECT_SOUND_PATH: &'static str = "assets/sfx/select.wav";
+/// By how much left and right change a volume.
+const VOLUME_STEP: f64 = 0.1;
+const BAR_W: f64 = 160.0;
+
+/// What the game should do once the player made a choice in the pause menu.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseAction {
+    None,
+    Resume,
+    Restart,
+    QuitToMenu,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Item {
+    Resume,
+    Options,
+    Restart,
+    QuitToMenu,
+    // In the options.
+    Master,
+    Music,
+    Effects,
+    Back,
+}
+
+const MAIN_ITEMS: [Item; 4] = [Item::Resume, Item::Options, Item::Restart, Item::QuitToMenu];
+const OPTION_ITEMS: [Item; 4] = [Item::Master, Item::Music, Item::Effects, Item::Back];
+
+impl Item {
+    fn label(self) -> &'static str {
+        match self {
+            Item::Resume => "Resume",
+            Item::Options => "Options",
+            Item::Restart => "Restart",
+            Item::QuitToMenu => "Quit to Menu",
+            Item::Master => "Master",
+            Item::Music => "Music",
+            Item::Effects => "Effects",
+            Item::Back => "Back",
+        }
+    }
+
+    /// The volume adjusted by the item, if any.
+    fn volume(self, volumes: &mut Volumes) -> Option<&mut f64> {
+        match self {
+            Item::Master => Some(&mut volumes.master),
+            Item::Music => Some(&mut volumes.music),
+            Item::Effects => Some(&mut volumes.sfx),
+            _ => None,
+        }
+    }
+}
+
+struct Entry {
+    item: Item,
+    idle_sprite: Sprite,
+    hover_sprite: Sprite,
+}
+
+impl Entry {
+    fn new(phi: &mut Phi, item: Item) -> Entry {
+        Entry {
+            item: item,
+            idle_sprite: phi.ttf_str_sprite(item.label(), FONT_PATH, 28, Color::RGB(220, 220, 220)).unwrap(),
+            hover_sprite: phi.ttf_str_sprite(item.label(), FONT_PATH, 28, Color::RGB(255, 255, 0)).unwrap(),
+        }
+    }
+}
+
+/// The menu shown over the game while it is paused. It lets the player resume,
+/// restart, go back to the main menu, or adjust the volumes.
+pub struct PauseMenu {
+    title: Sprite,
+    main: Vec<Entry>,
+    options: Vec<Entry>,
+    in_options: bool,
+    selected: usize,
+    // Played as volumes change, so that the player hears the difference.
+    step_sound: Option<Sound>,
+}
+
+impl PauseMenu {
+    pub fn new(phi: &mut Phi) -> PauseMenu {
+        PauseMenu {
+            title: phi.ttf_str_sprite("Paused", FONT_PATH, 48, Color::RGB(255, 255, 255)).unwrap(),
+            main: MAIN_ITEMS.iter().map(|&item| Entry::new(phi, item)).collect(),
+            options: OPTION_ITEMS.iter().map(|&item| Entry::new(phi, item)).collect(),
+            in_options: false,
+            selected: 0,
+            step_sound: phi.audio.load_sound(SELECT_SOUND_PATH),
+        }
+    }
+
+    /// Handles the input of the player. Escape resumes the game, or leaves
+    /// the options.
+    pub fn update(&mut self, phi: &mut Phi) -> PauseAction {
+        if phi.events.now.key_escape == Some(true) {
+            if self.in_options {
+                self.leave_options();
+                return PauseAction::None;
+            }
+
+            return PauseAction::Resume;
+        }
+
+        let len = self.entries().len();
+
+        if phi.events.now.key_up == Some(true) {
+            self.selected = (self.selected + len - 1) % len;
+        }
+
+        if phi.events.now.key_down == Some(true) {
+            self.selected = (self.selected + 1) % len;
+        }
+
+        let item = self.entries()[self.selected].item;
+
+        let step = match (phi.events.now.key_left, phi.events.now.key_right) {
+            (Some(true), _) => -VOLUME_STEP,
+            (_, Some(true)) => VOLUME_STEP,
+            _ => 0.0,
+        };
+
+        if step != 0.0 {
+            let mut volumes = phi.audio.volumes();
+            if let Some(volume) = item.volume(&mut volumes) {
+                *volume = (*volume + step).max(0.0).min(1.0);
+            }
+            phi.audio.set_volumes(volumes);
+
+            if let Some(sound) = self.step_sound {
+                phi.audio.play(sound);
+            }
+        }
+
+        if phi.events.now.key_space != Some(true) {
+            return PauseAction::None;
+        }
+
+        match item {
+            Item::Resume => PauseAction::Resume,
+            Item::Restart => PauseAction::Restart,
+            Item::QuitToMenu => PauseAction::QuitToMenu,
+            Item::Options => {
+                self.in_options = true;
+                self.selected = 0;
+                PauseAction::None
+            }
+            Item::Back => {
+                self.leave_options();
+                PauseAction::None
+            }
+            Item::Master | Item::Music | Item::Effects => PauseAction::None,
+        }
+    }
+
+    /// Dims whatever was drawn so far, then draws the menu over it.
+    pub fn render(&self, phi: &mut Phi) {
+        let (win_w, win_h) = phi.output_size();
+
+        phi.renderer.set_blend_mode(BlendMode::Blend);
+        phi.renderer.set_draw_color(Color::RGBA(0, 0, 0, 170));
+        let _ = phi.renderer.fill_rect(Rectangle {
+            x: 0.0,
+            y: 0.0,
+            w: win_w,
+            h: win_h,
+        }.to_sdl());
+        phi.renderer.set_blend_mode(BlendMode::None);
+
+        let (title_w, title_h) = self.title.size();
+        let mut y = win_h / 4.0;
+        phi.renderer.copy_sprite(&self.title, Rectangle {
+            x: (win_w - title_w) / 2.0,
+            y: y,
+            w: title_w,
+            h: title_h,
+        });
+        y += title_h + 24.0;
+
+        let volumes = phi.audio.volumes();
+
+        for (i, entry) in self.entries().iter().enumerate() {
+            let sprite = if i == self.selected { &entry.hover_sprite } else { &entry.idle_sprite };
+            let (w, h) = sprite.size();
+
+            // Volumes show up as a bar to the right of their label.
+            let mut volumes = volumes;
+            let level = entry.item.volume(&mut volumes).cloned();
+            let left = match level {
+                Some(_) => (win_w - w - BAR_W - 16.0) / 2.0,
+                None => (win_w - w) / 2.0,
+            };
+
+            phi.renderer.copy_sprite(sprite, Rectangle {
+                x: left,
+                y: y,
+                w: w,
+                h: h,
+            });
+
+            if let Some(level) = level {
+                let bar = Rectangle {
+                    x: left + w + 16.0,
+                    y: y + h / 2.0 - 5.0,
+                    w: BAR_W,
+                    h: 10.0,
+                };
+
+                phi.renderer.set_draw_color(Color::RGB(60, 60, 60));
+                let _ = phi.renderer.fill_rect(bar.to_sdl());
+
+                phi.renderer.set_draw_color(Color::RGB(255, 255, 0));
+                let _ = phi.renderer.fill_rect(Rectangle {
+                    w: BAR_W * level,
+                    ..bar
+                }.to_sdl());
+            }
+
+            y += h + 16.0;
+        }
+    }
+
+    fn entries(&self) -> &[Entry] {
+        if self.in_options { &self.options } else { &self.main }
+    }
+
+    fn leave_options(&mut self) {
+        self.in_options = false;
+        self.selected = MAIN_ITEMS.iter().position(|&item| item == Item::Options).unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+use phi::data::{Rectangle, Vec2};
+use phi::ecs::{Body, Collider, Drawable, Entity, Offscreen, Velocity, World};
+use rand::Rng;
+use sdl2::pixels::Color;
+use views::weapons::WeaponKind;
+
+const POWERUP_SIDE: f64 = 20.0;
+/// How fast dropped power-ups drift to the left, in pixels per second.
+const POWERUP_SPEED: f64 = 60.0;
+/// The collision layer of power-ups, for whatever wants to pick them up.
+pub const POWERUP_LAYER: u32 = 1;
+
+/// For how long, in seconds, each power-up lasts once collected.
+const WEAPON_DURATION: f64 = 12.0;
+const SHIELD_DURATION: f64 = 8.0;
+const SPEED_DURATION: f64 = 10.0;
+/// In game time, which goes by slower while the power-up is in effect.
+const BULLET_TIME_DURATION: f64 = 4.0;
+/// How much faster the ship flies while the speed power-up is in effect.
+const SPEED_BOOST: f64 = 1.5;
+/// How fast time goes by during bullet time. The ship itself keeps flying,
+/// firing and recovering from hits in real time.
+const BULLET_TIME_SCALE: f64 = 0.4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerUpKind {
+    Spread,
+    Rapid,
+    Laser,
+    Wave,
+    Missiles,
+    Shield,
+    Speed,
+    BulletTime,
+}
+
+/// Every kind of power-up, in the order of their declaration.
+pub const KINDS: [PowerUpKind; 8] = [
+    PowerUpKind::Spread,
+    PowerUpKind::Rapid,
+    PowerUpKind::Laser,
+    PowerUpKind::Wave,
+    PowerUpKind::Missiles,
+    PowerUpKind::Shield,
+    PowerUpKind::Speed,
+    PowerUpKind::BulletTime,
+];
+
+impl PowerUpKind {
+    pub fn duration(self) -> f64 {
+        match self {
+            PowerUpKind::Shield => SHIELD_DURATION,
+            PowerUpKind::Speed => SPEED_DURATION,
+            PowerUpKind::BulletTime => BULLET_TIME_DURATION,
+            _ => WEAPON_DURATION,
+        }
+    }
+
+    /// The weapon granted by the power-up, if any.
+    pub fn weapon(self) -> Option<WeaponKind> {
+        match self {
+            PowerUpKind::Spread => Some(WeaponKind::Spread),
+            PowerUpKind::Rapid => Some(WeaponKind::Rapid),
+            PowerUpKind::Laser => Some(WeaponKind::Laser),
+            PowerUpKind::Wave => Some(WeaponKind::Wave),
+            PowerUpKind::Missiles => Some(WeaponKind::Missiles),
+            PowerUpKind::Shield | PowerUpKind::Speed | PowerUpKind::BulletTime => None,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            PowerUpKind::Spread => Color::RGB(255, 160, 40),
+            PowerUpKind::Rapid => Color::RGB(255, 240, 60),
+            PowerUpKind::Laser => Color::RGB(90, 200, 255),
+            PowerUpKind::Wave => Color::RGB(140, 255, 200),
+            PowerUpKind::Missiles => Color::RGB(255, 80, 80),
+            PowerUpKind::Shield => Color::RGB(80, 255, 160),
+            PowerUpKind::Speed => Color::RGB(230, 90, 255),
+            PowerUpKind::BulletTime => Color::RGB(220, 230, 255),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerUpKind::Spread => "Spread",
+            PowerUpKind::Rapid => "Rapid",
+            PowerUpKind::Laser => "Laser",
+            PowerUpKind::Wave => "Wave",
+            PowerUpKind::Missiles => "Missiles",
+            PowerUpKind::Shield => "Shield",
+            PowerUpKind::Speed => "Speed",
+            PowerUpKind::BulletTime => "Bullet Time",
+        }
+    }
+}
+
+/// Rolls for a random power-up left behind by something destroyed at `rect`,
+/// which happens with probability `chance`. Power-ups are entities having
+/// their `PowerUpKind` as a component.
+pub fn drop<R: Rng>(world: &mut World, rng: &mut R, rect: Rectangle, chance: f64) -> Option<Entity> {
+    if rng.gen::<f64>() >= chance {
+        return None;
+    }
+
+    let kind = KINDS[rng.gen_range(0, KINDS.len())];
+    let powerup = world.spawn();
+
+    world.insert(powerup, kind);
+    world.insert(powerup, Body(Rectangle::from_center(rect.center(), POWERUP_SIDE, POWERUP_SIDE)));
+    world.insert(powerup, Velocity(Vec2::new(-POWERUP_SPEED, 0.0)));
+    world.insert(powerup, Offscreen);
+    world.insert(powerup, Collider {
+        layers: POWERUP_LAYER,
+        collides_with: 0,
+    });
+    world.insert(powerup, Drawable::Rect {
+        color: Color::RGB(20, 20, 40),
+        border: Some((kind.color(), 5.0)),
+    });
+
+    Some(powerup)
+}
+
+/// The power-ups in effect on the ship, and the time left to each of them.
+pub struct PowerUps {
+    // The power-up which granted the current weapon, if any.
+    weapon: Option<PowerUpKind>,
+    weapon_left: f64,
+    shield_left: f64,
+    speed_left: f64,
+    slow_left: f64,
+}
+
+impl PowerUps {
+    pub fn new() -> PowerUps {
+        PowerUps {
+            weapon: None,
+            weapon_left: 0.0,
+            shield_left: 0.0,
+            speed_left: 0.0,
+            slow_left: 0.0,
+        }
+    }
+
+    /// Applies a collected power-up. A new weapon replaces the current one,
+    /// while collecting a power-up already in effect restarts its timer.
+    pub fn collect(&mut self, kind: PowerUpKind) {
+        match kind {
+            PowerUpKind::Shield => self.shield_left = SHIELD_DURATION,
+            PowerUpKind::Speed => self.speed_left = SPEED_DURATION,
+            PowerUpKind::BulletTime => self.slow_left = BULLET_TIME_DURATION,
+            _ => {
+                self.weapon = Some(kind);
+                self.weapon_left = WEAPON_DURATION;
+            }
+        }
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.weapon_left = (self.weapon_left - dt).max(0.0);
+        self.shield_left = (self.shield_left - dt).max(0.0);
+        self.speed_left = (self.speed_left - dt).max(0.0);
+        self.slow_left = (self.slow_left - dt).max(0.0);
+
+        if self.weapon_left == 0.0 {
+            self.weapon = None;
+        }
+    }
+
+    pub fn weapon(&self) -> WeaponKind {
+        self.weapon.and_then(PowerUpKind::weapon).unwrap_or(WeaponKind::Twin)
+    }
+
+    pub fn is_shielded(&self) -> bool {
+        self.shield_left > 0.0
+    }
+
+    /// The time left to the shield, in seconds.
+    pub fn shield_left(&self) -> f64 {
+        self.shield_left
+    }
+
+    /// By how much the normal speed of the ship is multiplied.
+    pub fn speed_factor(&self) -> f64 {
+        if self.speed_left > 0.0 { SPEED_BOOST } else { 1.0 }
+    }
+
+    /// How fast time should go by in the game.
+    pub fn time_scale(&self) -> f64 {
+        if self.slow_left > 0.0 { BULLET_TIME_SCALE } else { 1.0 }
+    }
+
+    /// The power-ups in effect, along with the fraction of their duration
+    /// which is left.
+    pub fn active(&self) -> Vec<(PowerUpKind, f64)> {
+        let mut active = vec![];
+
+        if let Some(kind) = self.weapon {
+            active.push((kind, self.weapon_left / kind.duration()));
+        }
+
+        if self.shield_left > 0.0 {
+            active.push((PowerUpKind::Shield, self.shield_left / SHIELD_DURATION));
+        }
+
+        if self.speed_left > 0.0 {
+            active.push((PowerUpKind::Speed, self.speed_left / SPEED_DURATION));
+        }
+
+        if self.slow_left > 0.0 {
+            active.push((PowerUpKind::BulletTime, self.slow_left / BULLET_TIME_DURATION));
+        }
+
+        active
+    }
+
+    /// Loses every power-up, as happens when the ship is destroyed.
+    pub fn clear(&mut self) {
+        *self = PowerUps::new();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_ups_expire_on_their_own() {
+        let mut powers = PowerUps::new();
+        powers.collect(PowerUpKind::Laser);
+        powers.collect(PowerUpKind::Shield);
+        powers.collect(PowerUpKind::Speed);
+        powers.collect(PowerUpKind::BulletTime);
+
+        assert_eq!(powers.weapon(), WeaponKind::Laser);
+        assert!(powers.is_shielded());
+        assert_eq!(powers.speed_factor(), SPEED_BOOST);
+        assert_eq!(powers.time_scale(), BULLET_TIME_SCALE);
+
+        powers.update(BULLET_TIME_DURATION);
+        assert_eq!(powers.time_scale(), 1.0);
+        assert_eq!(powers.active().len(), 3);
+
+        powers.update(SHIELD_DURATION - BULLET_TIME_DURATION);
+        assert!(!powers.is_shielded());
+        assert_eq!(powers.shield_left(), 0.0);
+
+        powers.update(SPEED_DURATION - SHIELD_DURATION);
+        assert_eq!(powers.speed_factor(), 1.0);
+
+        powers.update(WEAPON_DURATION - SPEED_DURATION);
+        assert_eq!(powers.weapon(), WeaponKind::Twin);
+        assert_eq!(powers.active(), vec![]);
+    }
+
+    #[test]
+    fn collecting_again_refreshes_the_timer() {
+        let mut powers = PowerUps::new();
+        powers.collect(PowerUpKind::Shield);
+        powers.update(SHIELD_DURATION - 1.0);
+        assert_eq!(powers.active(), vec![(PowerUpKind::Shield, 1.0 / SHIELD_DURATION)]);
+
+        powers.collect(PowerUpKind::Shield);
+        assert_eq!(powers.shield_left(), SHIELD_DURATION);
+        powers.update(SHIELD_DURATION - 1.0);
+        assert!(powers.is_shielded());
+    }
+
+    #[test]
+    fn new_weapons_replace_the_current_one() {
+        let mut powers = PowerUps::new();
+        powers.collect(PowerUpKind::Spread);
+        powers.update(WEAPON_DURATION / 2.0);
+
+        powers.collect(PowerUpKind::Missiles);
+        assert_eq!(powers.weapon(), WeaponKind::Missiles);
+        assert_eq!(powers.active(), vec![(PowerUpKind::Missiles, 1.0)]);
+
+        // Other power-ups leave the weapon alone.
+        powers.collect(PowerUpKind::Speed);
+        assert_eq!(powers.weapon(), WeaponKind::Missiles);
+        assert_eq!(powers.active().len(), 2);
+    }
+
+    #[test]
+    fn losing_a_life_loses_every_power_up() {
+        let mut powers = PowerUps::new();
+        for &kind in &KINDS {
+            powers.collect(kind);
+        }
+
+        powers.clear();
+        assert_eq!(powers.weapon(), WeaponKind::Twin);
+        assert!(!powers.is_shielded());
+        assert_eq!(powers.speed_factor(), 1.0);
+        assert_eq!(powers.time_scale(), 1.0);
+        assert_eq!(powers.active(), vec![]);
+    }
+}
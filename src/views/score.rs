@@ -0,0 +1,135 @@
+/// The highest multiplier a combo can reach.
+const MAX_MULTIPLIER: u32 = 8;
+/// How long, in seconds, the player has to score another kill before the
+/// multiplier drops by one step.
+const COMBO_WINDOW: f64 = 2.0;
+
+/// Keeps track of the points of the player. Every kill raises the multiplier
+/// applied to the next ones, which decays again if the player stops scoring.
+pub struct Score {
+    points: u32,
+    multiplier: u32,
+    // The time left before the multiplier decays.
+    combo_timer: f64,
+}
+
+impl Score {
+    pub fn new() -> Score {
+        Score {
+            points: 0,
+            multiplier: 1,
+            combo_timer: 0.0,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    pub fn multiplier(&self) -> u32 {
+        self.multiplier
+    }
+
+    /// The fraction of the combo window left before the multiplier decays.
+    pub fn combo_left(&self) -> f64 {
+        self.combo_timer / COMBO_WINDOW
+    }
+
+    /// Awards the points of a kill, multiplied by the current combo, and
+    /// raises the multiplier. Returns the amount of points awarded.
+    pub fn kill(&mut self, points: u32) -> u32 {
+        let awarded = points * self.multiplier;
+        self.points += awarded;
+        self.multiplier = (self.multiplier + 1).min(MAX_MULTIPLIER);
+        self.combo_timer = COMBO_WINDOW;
+        awarded
+    }
+
+    /// Breaks the current combo, for example when the player gets hit.
+    pub fn reset_combo(&mut self) {
+        self.multiplier = 1;
+        self.combo_timer = 0.0;
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        if self.multiplier == 1 {
+            return;
+        }
+
+        self.combo_timer -= dt;
+        if self.combo_timer <= 0.0 {
+            self.multiplier -= 1;
+            self.combo_timer = if self.multiplier > 1 { COMBO_WINDOW } else { 0.0 };
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kills_raise_the_multiplier_up_to_its_cap() {
+        let mut score = Score::new();
+
+        assert_eq!(score.kill(100), 100);
+        assert_eq!(score.multiplier(), 2);
+        assert_eq!(score.kill(100), 200);
+        assert_eq!(score.kill(50), 150);
+        assert_eq!(score.points(), 450);
+
+        for _ in 0..20 {
+            score.kill(10);
+        }
+
+        assert_eq!(score.multiplier(), MAX_MULTIPLIER);
+        assert_eq!(score.kill(10), 10 * MAX_MULTIPLIER);
+    }
+
+    #[test]
+    fn the_multiplier_decays_one_step_per_window() {
+        let mut score = Score::new();
+        score.kill(1);
+        score.kill(1);
+        assert_eq!(score.multiplier(), 3);
+        assert_eq!(score.combo_left(), 1.0);
+
+        score.update(COMBO_WINDOW / 4.0);
+        assert_eq!(score.multiplier(), 3);
+        assert_eq!(score.combo_left(), 0.75);
+
+        score.update(COMBO_WINDOW * 3.0 / 4.0);
+        assert_eq!(score.multiplier(), 2);
+        assert_eq!(score.combo_left(), 1.0);
+
+        // Every kill starts the window over.
+        score.update(COMBO_WINDOW / 2.0);
+        score.kill(1);
+        score.update(COMBO_WINDOW * 3.0 / 4.0);
+        assert_eq!(score.multiplier(), 3);
+
+        score.update(COMBO_WINDOW / 4.0);
+        score.update(COMBO_WINDOW);
+        assert_eq!(score.multiplier(), 1);
+        assert_eq!(score.combo_left(), 0.0);
+
+        // The multiplier never drops below one, and points are kept.
+        score.update(COMBO_WINDOW * 10.0);
+        assert_eq!(score.multiplier(), 1);
+        assert_eq!(score.points(), 1 + 2 + 2);
+    }
+
+    #[test]
+    fn resetting_the_combo_keeps_the_points() {
+        let mut score = Score::new();
+        score.kill(100);
+        score.kill(100);
+
+        score.reset_combo();
+        assert_eq!(score.multiplier(), 1);
+        assert_eq!(score.combo_left(), 0.0);
+        assert_eq!(score.points(), 300);
+        assert_eq!(score.kill(100), 100);
+    }
+}
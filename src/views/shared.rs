@@ -1,75 +1,132 @@
-use phi::data::Rectangle;
-use phi::gfx::{CopySprite, Sprite};
+use phi::data::{Rectangle, Vec2};
+use phi::gfx::Sprite;
+use sdl2::pixels::Color;
 use sdl2::render::Renderer;
 
 
+/// How a layer covers the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tiling {
+    /// Scaled to the height of the screen and repeated to fill it.
+    FitHeight,
+    /// Repeated at the size of the image, so that it can scroll vertically
+    /// without being stretched.
+    Native,
+}
+
+/// Whether a layer is drawn behind or in front of the ships, asteroids and
+/// bullets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Depth {
+    Behind,
+    InFront,
+}
+
+/// Describes a layer of a parallax stack, as listed by the scenery files
+/// loaded through `level::load_scenery`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerConfig {
+    pub path: String,
+    /// The amount of pixels of the image moved to the left and to the bottom
+    /// every second. Layers which seem further away should move slower.
+    pub velocity: (f64, f64),
+    pub tint: Color,
+    pub tiling: Tiling,
+    pub depth: Depth,
+}
+
 #[derive(Clone)]
-pub struct Background {
-    pub pos: f64,
-    // The amount of pixels moved to the left every second
-    pub vel: f64,
-    pub sprite: Sprite,
+struct Layer {
+    config: LayerConfig,
+    sprite: Sprite,
+    pos: Vec2,
 }
 
-impl Background {
-    pub fn render(&mut self, renderer: &mut Renderer, elapsed: f64) {
+impl Layer {
+    fn update(&mut self, dt: f64) {
         // We define a logical position as depending solely on the time and the
         // dimensions of the image, not on the screen's size.
-        let size = self.sprite.size();
-        self.pos += self.vel * elapsed;
-        if self.pos > size.0 {
-            self.pos -= size.0;
-        }
+        let (w, h) = self.sprite.size();
+        let (vel_x, vel_y) = self.config.velocity;
+        self.pos = Vec2::new(
+            (self.pos.x + vel_x * dt).rem_euclid(w),
+            (self.pos.y + vel_y * dt).rem_euclid(h));
+    }
 
-        // We determine the scale ratio of the window to the sprite.
+    fn render(&self, renderer: &mut Renderer) {
+        let (w, h) = self.sprite.size();
         let (win_w, win_h) = renderer.output_size().unwrap();
-        let scale = win_h as f64 / size.1;
-
-        // We render as many copies of the background as necessary to fill
-        // the screen.
-        let mut physical_left = -self.pos * scale;
-
-        while physical_left < win_w as f64 {
-            renderer.copy_sprite(&self.sprite, Rectangle {
-                x: physical_left,
-                y: 0.0,
-                w: size.0 * scale,
-                h: win_h as f64,
-            });
+        let (win_w, win_h) = (win_w as f64, win_h as f64);
+        let scale = match self.config.tiling {
+            Tiling::FitHeight => win_h / h,
+            Tiling::Native => 1.0,
+        };
+
+        // We render as many copies of the layer as necessary to fill the
+        // screen, starting from the one covering its top-left corner.
+        let (tile_w, tile_h) = (w * scale, h * scale);
+        let left = -self.pos.x * scale;
+        let mut top = (self.pos.y - h) * scale;
+
+        while top < win_h {
+            let mut x = left;
+
+            while x < win_w {
+                self.sprite.render_tinted(renderer, Rectangle {
+                    x: x,
+                    y: top,
+                    w: tile_w,
+                    h: tile_h,
+                }, self.config.tint);
 
-            physical_left += size.0 * scale;
+                x += tile_w;
+            }
+
+            top += tile_h;
         }
     }
 }
 
-
-/// Utility structure to reduce the number of arguments certain methods accept,
-/// and the amount of `clone` calls required when switching views.
+/// Layers of images scrolling at different speeds, which gives an illusion
+/// of depth. They are cloned when switching views, so that the background
+/// carries on from where it was.
 #[derive(Clone)]
-pub struct BgSet {
-    pub back: Background,
-    pub middle: Background,
-    pub front: Background,
+pub struct Parallax {
+    // In drawing order.
+    layers: Vec<Layer>,
 }
 
-impl BgSet {
-    pub fn new(renderer: &mut Renderer) -> BgSet {
-        BgSet {
-            back: Background {
-                pos: 0.0,
-                vel: 20.0,
-                sprite: Sprite::load(renderer, "assets/starBG.png").unwrap(),
-            },
-            middle: Background {
-                pos: 0.0,
-                vel: 40.0,
-                sprite: Sprite::load(renderer, "assets/starMG.png").unwrap(),
-            },
-            front: Background {
-                pos: 0.0,
-                vel: 80.0,
-                sprite: Sprite::load(renderer, "assets/starFG.png").unwrap(),
-            },
+impl Parallax {
+    /// Loads the images of the layers, failing if one is missing.
+    pub fn new(renderer: &mut Renderer, layers: &[LayerConfig]) -> Result<Parallax, String> {
+        let mut loaded = Vec::with_capacity(layers.len());
+
+        for config in layers {
+            let sprite = Sprite::load(renderer, &config.path)
+                .ok_or_else(|| format!("cannot load the image {}", config.path))?;
+
+            loaded.push(Layer {
+                config: config.clone(),
+                sprite: sprite,
+                pos: Vec2::default(),
+            });
+        }
+
+        Ok(Parallax { layers: loaded })
+    }
+
+    /// Scrolls the layers by the time elapsed in the game, so that they stop
+    /// along with it.
+    pub fn update(&mut self, dt: f64) {
+        for layer in &mut self.layers {
+            layer.update(dt);
+        }
+    }
+
+    /// Draws the layers at the given depth.
+    pub fn render(&self, renderer: &mut Renderer, depth: Depth) {
+        for layer in self.layers.iter().filter(|layer| layer.config.depth == depth) {
+            layer.render(renderer);
         }
     }
 }
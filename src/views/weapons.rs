@@ -0,0 +1,682 @@
+use phi::Phi;
+use phi::data::{Rectangle, Vec2};
+use phi::ecs::{Body, Collider, Contacts, Drawable, Entity, Lifetime, Offscreen, Velocity, World};
+use phi::gfx::Sprite;
+use sdl2::pixels::Color;
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+use views::asteroids::ASTEROID_LAYER;
+use views::boss::BOSS_LAYER;
+use views::enemies::ENEMY_LAYER;
+
+const BULLETS_PATH: &'static str = "assets/bullets.png";
+/// Where each kind of bullet is found on the sprite sheet.
+const BOLT_REGION: Rectangle = Rectangle { x: 0.0, y: 0.0, w: 16.0, h: 6.0 };
+const ORB_REGION: Rectangle = Rectangle { x: 16.0, y: 0.0, w: 12.0, h: 12.0 };
+const MISSILE_REGION: Rectangle = Rectangle { x: 32.0, y: 0.0, w: 16.0, h: 8.0 };
+
+const BOLT_SPEED: f64 = 300.0;
+const BOLT_LIFETIME: f64 = 4.0;
+const WAVE_SPEED: f64 = 220.0;
+const WAVE_AMPLITUDE: f64 = 40.0;
+/// How many times per second wave bullets go up and down.
+const WAVE_FREQUENCY: f64 = 1.5;
+const WAVE_LIFETIME: f64 = 5.0;
+const MISSILE_SPEED: f64 = 260.0;
+/// How fast missiles turn towards their target, in radians per second.
+const MISSILE_TURN_RATE: f64 = 4.0;
+const MISSILE_LIFETIME: f64 = 3.0;
+/// Missiles leave puffs of smoke this far behind them, which last for this
+/// long.
+const SMOKE_DISTANCE: f64 = 12.0;
+const SMOKE_LIFETIME: f64 = 0.05;
+/// For how long the beam of the laser stays visible after being fired.
+const BEAM_LIFETIME: f64 = 0.12;
+const BEAM_H: f64 = 6.0;
+
+/// The layers of what the shots of the ship can hit.
+pub const TARGET_LAYERS: u32 = ASTEROID_LAYER | ENEMY_LAYER | BOSS_LAYER;
+
+/// Hurts whatever the entity hits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Damage {
+    pub amount: u32,
+    /// Whether the projectile goes through what it hits instead of being
+    /// consumed.
+    pub pierces: bool,
+}
+
+/// The hit points left to something which can be shot down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Health(pub u32);
+
+/// Moves a bullet forward along a sine wave.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wave {
+    origin: Vec2,
+    // Makes bullets of the same shot weave in opposite directions.
+    phase: f64,
+    time: f64,
+}
+
+impl Wave {
+    fn center(&self) -> Vec2 {
+        let wave = (2.0 * PI * WAVE_FREQUENCY * self.time + self.phase).sin() * WAVE_AMPLITUDE;
+        self.origin + Vec2::new(WAVE_SPEED * self.time, wave)
+    }
+}
+
+/// Steers a missile towards the closest target it can hit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Homing;
+
+/// A beam reaching the right edge of the screen at once. It hits everything
+/// in its way on the frame after being fired, then only lingers on screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Beam {
+    rect: Rectangle,
+    // Whether the beam went through its first frame already. Counting frames,
+    // rather than looking at its age, keeps the beam from hitting again
+    // during freeze frames, through which no time passes.
+    fired: bool,
+}
+
+/// Turns a press of the trigger into projectiles.
+pub trait Weapon {
+    /// The time, in seconds, between two shots while the trigger is held.
+    fn cooldown(&self) -> f64;
+
+    /// Spawns the projectiles of a single shot fired from a ship occupying
+    /// `ship`.
+    fn fire(&self, world: &mut World, ship: Rectangle) -> Vec<Entity>;
+}
+
+/// Every weapon the ship can be armed with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeaponKind {
+    /// The default pair of bolts.
+    Twin,
+    /// The twin bolts, plus two more fanning out.
+    Spread,
+    /// The twin bolts, fired much faster.
+    Rapid,
+    /// A beam going through everything in front of the ship.
+    Laser,
+    /// Bullets weaving up and down.
+    Wave,
+    /// Missiles seeking the closest target.
+    Missiles,
+}
+
+/// How a kind of bullet is drawn, and the size of its body.
+#[derive(Clone)]
+struct Look {
+    drawable: Drawable,
+    size: (f64, f64),
+}
+
+impl Look {
+    fn sprite(sprite: Sprite) -> Look {
+        Look {
+            size: sprite.size(),
+            drawable: Drawable::Sprite(sprite),
+        }
+    }
+
+    /// Spawns a bullet centered on `center`, which is consumed by the first
+    /// target it hits.
+    fn spawn(&self, world: &mut World, center: Vec2, damage: u32) -> Entity {
+        let bullet = world.spawn();
+        world.insert(bullet, Body(Rectangle::from_center(center, self.size.0, self.size.1)));
+        world.insert(bullet, self.drawable.clone());
+        world.insert(bullet, Offscreen);
+        world.insert(bullet, Collider {
+            layers: 0,
+            collides_with: TARGET_LAYERS,
+        });
+        world.insert(bullet, Damage {
+            amount: damage,
+            pierces: false,
+        });
+        bullet
+    }
+}
+
+/// Applies the damage of the projectiles which touched a target during the
+/// last collision pass, and returns the targets they destroyed. Projectiles
+/// are despawned as they hit their first target, unless they pierce through
+/// everything. Destroyed targets are left in the world, for whoever destroyed
+/// them to clean up.
+pub fn hit(world: &mut World) -> Vec<Entity> {
+    let contacts = match world.resource::<Contacts>() {
+        Some(&Contacts(ref contacts)) => contacts.clone(),
+        None => return vec![],
+    };
+
+    let mut spent = vec![];
+    let mut destroyed = vec![];
+
+    for (projectile, target) in contacts {
+        let damage = match world.get::<Damage>(projectile) {
+            Some(&damage) if !spent.contains(&projectile) => damage,
+            _ => continue,
+        };
+
+        // Targets without any health soak up projectiles without being hurt.
+        match world.get_mut::<Health>(target) {
+            Some(&mut Health(0)) => continue,
+            Some(&mut Health(ref mut hp)) => {
+                *hp = hp.saturating_sub(damage.amount);
+                if *hp == 0 {
+                    destroyed.push(target);
+                }
+            }
+            None => {}
+        }
+
+        if !damage.pierces {
+            spent.push(projectile);
+            world.despawn(projectile);
+        }
+    }
+
+    destroyed
+}
+
+/// Moves wave bullets along their wave.
+pub fn weave(world: &mut World, _: &mut Phi, dt: f64) {
+    for entity in world.with::<Wave>() {
+        let center = {
+            let wave = world.get_mut::<Wave>(entity).unwrap();
+            wave.time += dt;
+            wave.center()
+        };
+
+        if let Some(&mut Body(ref mut rect)) = world.get_mut::<Body>(entity) {
+            *rect = Rectangle::from_center(center, rect.w, rect.h);
+        }
+    }
+}
+
+/// Steers missiles towards the closest target on screen.
+pub fn home(world: &mut World, phi: &mut Phi, dt: f64) {
+    let (w, h) = phi.output_size();
+    steer(world, dt, Rectangle { x: 0.0, y: 0.0, w: w, h: h });
+}
+
+fn steer(world: &mut World, dt: f64, screen: Rectangle) {
+    let targets: Vec<(u32, Rectangle)> = world.with::<Collider>().into_iter()
+        .filter_map(|entity| {
+            let layers = world.get::<Collider>(entity).unwrap().layers;
+            world.get::<Body>(entity).map(|&Body(rect)| (layers, rect))
+        })
+        .collect();
+
+    for missile in world.with::<Homing>() {
+        let (pos, vel, wanted) = match (world.get::<Body>(missile),
+                                        world.get::<Velocity>(missile),
+                                        world.get::<Collider>(missile)) {
+            (Some(&Body(rect)), Some(&Velocity(vel)), Some(collider)) => {
+                (rect.center(), vel, collider.collides_with)
+            }
+            _ => continue,
+        };
+
+        let distance = |rect: &Rectangle| (rect.center() - pos).length();
+
+        // Only targets on screen and ahead of the missile are worth chasing.
+        let closest = targets.iter()
+            .filter(|&&(layers, rect)| {
+                layers & wanted != 0 && screen.contains_point(rect.center()) && rect.x + rect.w > pos.x
+            })
+            .map(|&(_, rect)| rect)
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap_or(Ordering::Equal));
+
+        let mut angle = vel.y.atan2(vel.x);
+
+        if let Some(target) = closest {
+            let to_target = target.center() - pos;
+            let wanted = to_target.y.atan2(to_target.x);
+
+            // Turn the shortest way round, without overshooting.
+            let mut delta = wanted - angle;
+            while delta > PI { delta -= 2.0 * PI; }
+            while delta < -PI { delta += 2.0 * PI; }
+
+            let max_turn = MISSILE_TURN_RATE * dt;
+            angle += delta.max(-max_turn).min(max_turn);
+        }
+
+        let heading = Vec2::new(angle.cos(), angle.sin());
+        world.insert(missile, Velocity(heading * vel.length()));
+
+        // Sprites cannot be rotated, so puffs of smoke behind the missile
+        // show where it is heading.
+        if dt > 0.0 {
+            let smoke = world.spawn();
+            world.insert(smoke, Body(Rectangle::from_center(pos - heading * SMOKE_DISTANCE, 4.0, 4.0)));
+            world.insert(smoke, Drawable::Rect { color: Color::RGB(150, 150, 150), border: None });
+            world.insert(smoke, Lifetime(SMOKE_LIFETIME));
+        }
+    }
+}
+
+/// Lets beams hit only on the frame after being fired, and narrows them as
+/// they fade out.
+pub fn discharge(world: &mut World, _: &mut Phi, _: f64) {
+    discharge_beams(world);
+}
+
+fn discharge_beams(world: &mut World) {
+    for entity in world.with::<Beam>() {
+        let (rect, first) = {
+            let beam = world.get_mut::<Beam>(entity).unwrap();
+            let first = !beam.fired;
+            beam.fired = true;
+            (beam.rect, first)
+        };
+
+        if !first {
+            world.remove::<Collider>(entity);
+        }
+
+        let left = world.get::<Lifetime>(entity).map_or(0.0, |&Lifetime(left)| left) / BEAM_LIFETIME;
+        let h = rect.h * left.max(0.0).min(1.0);
+
+        world.insert(entity, Body(Rectangle {
+            y: rect.y + (rect.h - h) / 2.0,
+            h: h,
+            ..rect
+        }));
+        world.insert(entity, Drawable::Rect {
+            color: Color::RGB(230, 250, 255),
+            border: Some((Color::RGB(90, 200, 255), h / 3.0)),
+        });
+    }
+}
+
+/// Fires a bolt from each cannon at once, at the given angles off the
+/// horizontal, in radians.
+pub struct Cannons {
+    look: Look,
+    angles: &'static [f64],
+    cooldown: f64,
+}
+
+impl Weapon for Cannons {
+    fn cooldown(&self) -> f64 {
+        self.cooldown
+    }
+
+    fn fire(&self, world: &mut World, ship: Rectangle) -> Vec<Entity> {
+        let x = ship.x + 30.0;
+        let mut bullets = vec![];
+
+        // The straight bolts come out of the top and bottom cannons, the
+        // others out of the nose.
+        for &angle in self.angles {
+            let ys: &[f64] = if angle == 0.0 { &[6.0, ship.h - 10.0] } else { &[ship.h / 2.0] };
+
+            for &y in ys {
+                let bullet = self.look.spawn(world, Vec2::new(x, ship.y + y), 1);
+                world.insert(bullet, Velocity(Vec2::new(angle.cos(), angle.sin()) * BOLT_SPEED));
+                world.insert(bullet, Lifetime(BOLT_LIFETIME));
+                bullets.push(bullet);
+            }
+        }
+
+        bullets
+    }
+}
+
+pub struct WaveGun {
+    look: Look,
+}
+
+impl Weapon for WaveGun {
+    fn cooldown(&self) -> f64 {
+        0.3
+    }
+
+    fn fire(&self, world: &mut World, ship: Rectangle) -> Vec<Entity> {
+        let center = ship.center();
+
+        [0.0, PI].iter().map(|&phase| {
+            let bullet = self.look.spawn(world, center, 1);
+            world.insert(bullet, Lifetime(WAVE_LIFETIME));
+            world.insert(bullet, Wave {
+                origin: center,
+                phase: phase,
+                time: 0.0,
+            });
+            bullet
+        }).collect()
+    }
+}
+
+pub struct MissileLauncher {
+    look: Look,
+}
+
+impl Weapon for MissileLauncher {
+    fn cooldown(&self) -> f64 {
+        0.5
+    }
+
+    fn fire(&self, world: &mut World, ship: Rectangle) -> Vec<Entity> {
+        // One missile leaves from each side of the ship, angled outwards.
+        [(6.0, -0.6), (ship.h - 6.0, 0.6)].iter().map(|&(y, angle): &(f64, f64)| {
+            let missile = self.look.spawn(world, Vec2::new(ship.x + ship.w / 2.0, ship.y + y), 2);
+            world.insert(missile, Velocity(Vec2::new(angle.cos(), angle.sin()) * MISSILE_SPEED));
+            world.insert(missile, Lifetime(MISSILE_LIFETIME));
+            world.insert(missile, Homing);
+            missile
+        }).collect()
+    }
+}
+
+pub struct LaserGun {
+    // The width of the beam, which is the width of the screen.
+    reach: f64,
+}
+
+impl Weapon for LaserGun {
+    fn cooldown(&self) -> f64 {
+        0.4
+    }
+
+    fn fire(&self, world: &mut World, ship: Rectangle) -> Vec<Entity> {
+        let x = ship.x + ship.w - 4.0;
+        let rect = Rectangle {
+            x: x,
+            y: ship.y + (ship.h - BEAM_H) / 2.0,
+            w: self.reach - x,
+            h: BEAM_H,
+        };
+
+        let beam = world.spawn();
+        world.insert(beam, Body(rect));
+        world.insert(beam, Lifetime(BEAM_LIFETIME));
+        world.insert(beam, Beam {
+            rect: rect,
+            fired: false,
+        });
+        world.insert(beam, Collider {
+            layers: 0,
+            collides_with: TARGET_LAYERS,
+        });
+        world.insert(beam, Damage {
+            amount: 3,
+            pierces: true,
+        });
+        vec![beam]
+    }
+}
+
+const TWIN_ANGLES: &'static [f64] = &[0.0];
+const SPREAD_ANGLES: &'static [f64] = &[0.0, -0.25, 0.25];
+
+/// Loads the bullet sprites once and holds an instance of every weapon.
+pub struct Arsenal {
+    twin: Cannons,
+    spread: Cannons,
+    rapid: Cannons,
+    laser: LaserGun,
+    wave: WaveGun,
+    missiles: MissileLauncher,
+}
+
+impl Arsenal {
+    pub fn new(phi: &mut Phi) -> Arsenal {
+        let sheet = Sprite::load(&mut phi.renderer, BULLETS_PATH).unwrap();
+
+        Arsenal::with_looks(Look::sprite(sheet.region(BOLT_REGION).unwrap()),
+                            Look::sprite(sheet.region(ORB_REGION).unwrap()),
+                            Look::sprite(sheet.region(MISSILE_REGION).unwrap()),
+                            phi.output_size().0)
+    }
+
+    /// `reach` is the width of the screen.
+    fn with_looks(bolt: Look, orb: Look, missile: Look, reach: f64) -> Arsenal {
+        Arsenal {
+            twin: Cannons { look: bolt.clone(), angles: TWIN_ANGLES, cooldown: 0.3 },
+            spread: Cannons { look: bolt.clone(), angles: SPREAD_ANGLES, cooldown: 0.35 },
+            rapid: Cannons { look: bolt, angles: TWIN_ANGLES, cooldown: 0.1 },
+            laser: LaserGun { reach: reach },
+            wave: WaveGun { look: orb },
+            missiles: MissileLauncher { look: missile },
+        }
+    }
+
+    pub fn get(&self, kind: WeaponKind) -> &dyn Weapon {
+        match kind {
+            WeaponKind::Twin => &self.twin,
+            WeaponKind::Spread => &self.spread,
+            WeaponKind::Rapid => &self.rapid,
+            WeaponKind::Laser => &self.laser,
+            WeaponKind::Wave => &self.wave,
+            WeaponKind::Missiles => &self.missiles,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phi::ecs::Collisions;
+    use views::patterns::ENEMY_BULLET_LAYER;
+
+    const SHIP: Rectangle = Rectangle { x: 100.0, y: 200.0, w: 43.0, h: 39.0 };
+    const SCREEN: Rectangle = Rectangle { x: 0.0, y: 0.0, w: 800.0, h: 600.0 };
+
+    fn arsenal() -> Arsenal {
+        let look = Look {
+            drawable: Drawable::Rect { color: Color::RGB(255, 255, 255), border: None },
+            size: (8.0, 4.0),
+        };
+
+        Arsenal::with_looks(look.clone(), look.clone(), look, SCREEN.w)
+    }
+
+    fn target(world: &mut World, rect: Rectangle, layers: u32, hp: Option<u32>) -> Entity {
+        let target = world.spawn();
+        world.insert(target, Body(rect));
+        world.insert(target, Collider { layers: layers, collides_with: 0 });
+        if let Some(hp) = hp {
+            world.insert(target, Health(hp));
+        }
+        target
+    }
+
+    fn enemy(world: &mut World, x: f64, y: f64, hp: u32) -> Entity {
+        target(world, Rectangle { x: x, y: y, w: 20.0, h: 20.0 }, ENEMY_LAYER, Some(hp))
+    }
+
+    fn bullet(world: &mut World, damage: u32, pierces: bool) -> Entity {
+        let bullet = world.spawn();
+        world.insert(bullet, Damage { amount: damage, pierces: pierces });
+        bullet
+    }
+
+    fn angle(world: &World, entity: Entity) -> f64 {
+        let Velocity(vel) = *world.get::<Velocity>(entity).unwrap();
+        vel.y.atan2(vel.x)
+    }
+
+    fn missile(world: &mut World, center: Vec2) -> Entity {
+        let missile = target(world, Rectangle::from_center(center, 16.0, 8.0), 0, None);
+        world.insert(missile, Collider { layers: 0, collides_with: TARGET_LAYERS });
+        world.insert(missile, Velocity(Vec2::new(MISSILE_SPEED, 0.0)));
+        world.insert(missile, Homing);
+        missile
+    }
+
+    #[test]
+    fn stronger_weapons_fire_slower() {
+        let arsenal = arsenal();
+        let cooldown = |kind| arsenal.get(kind).cooldown();
+
+        assert!(cooldown(WeaponKind::Rapid) < cooldown(WeaponKind::Twin));
+        assert!(cooldown(WeaponKind::Twin) < cooldown(WeaponKind::Spread));
+        assert!(cooldown(WeaponKind::Spread) < cooldown(WeaponKind::Missiles));
+        assert!(cooldown(WeaponKind::Twin) < cooldown(WeaponKind::Laser));
+    }
+
+    #[test]
+    fn spread_fans_two_bolts_out_of_the_nose() {
+        let mut world = World::new();
+        let bolts = arsenal().get(WeaponKind::Spread).fire(&mut world, SHIP);
+
+        assert_eq!(bolts.len(), 4);
+
+        let mut angles: Vec<f64> = bolts.iter().map(|&bolt| angle(&world, bolt)).collect();
+        angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for (angle, expected) in angles.into_iter().zip(vec![-0.25, 0.0, 0.0, 0.25]) {
+            assert!((angle - expected).abs() < 1e-9, "{} instead of {}", angle, expected);
+        }
+
+        for &bolt in &bolts {
+            let Velocity(vel) = *world.get::<Velocity>(bolt).unwrap();
+            assert!((vel.length() - BOLT_SPEED).abs() < 1e-9);
+            assert_eq!(world.get::<Damage>(bolt), Some(&Damage { amount: 1, pierces: false }));
+
+            let Body(rect) = *world.get::<Body>(bolt).unwrap();
+            let from_nose = rect.center().y == SHIP.y + SHIP.h / 2.0;
+            assert_eq!(from_nose, angle(&world, bolt) != 0.0);
+        }
+    }
+
+    #[test]
+    fn missiles_hit_harder_and_home() {
+        let mut world = World::new();
+        let missiles = arsenal().get(WeaponKind::Missiles).fire(&mut world, SHIP);
+
+        assert_eq!(missiles.len(), 2);
+        for &missile in &missiles {
+            assert_eq!(world.get::<Damage>(missile), Some(&Damage { amount: 2, pierces: false }));
+            assert_eq!(world.get::<Homing>(missile), Some(&Homing));
+        }
+    }
+
+    #[test]
+    fn projectiles_are_consumed_by_the_first_target_they_hit() {
+        let mut world = World::new();
+        let (first, second) = (enemy(&mut world, 0.0, 0.0, 3), enemy(&mut world, 0.0, 0.0, 1));
+        let bolt = bullet(&mut world, 1, false);
+        world.insert_resource(Contacts(vec![(bolt, first), (bolt, second)]));
+
+        assert_eq!(hit(&mut world), vec![]);
+        assert_eq!(world.get::<Health>(first), Some(&Health(2)));
+        assert_eq!(world.get::<Health>(second), Some(&Health(1)));
+
+        world.maintain();
+        assert!(!world.is_alive(bolt));
+    }
+
+    #[test]
+    fn piercing_projectiles_hurt_everything_they_touch() {
+        let mut world = World::new();
+        let (first, second) = (enemy(&mut world, 0.0, 0.0, 3), enemy(&mut world, 0.0, 0.0, 5));
+        let beam = bullet(&mut world, 3, true);
+        world.insert_resource(Contacts(vec![(beam, first), (beam, second)]));
+
+        assert_eq!(hit(&mut world), vec![first]);
+        assert_eq!(world.get::<Health>(first), Some(&Health(0)));
+        assert_eq!(world.get::<Health>(second), Some(&Health(2)));
+
+        world.maintain();
+        assert!(world.is_alive(beam));
+    }
+
+    #[test]
+    fn destroyed_targets_let_projectiles_through() {
+        let mut world = World::new();
+        let (dead, alive) = (enemy(&mut world, 0.0, 0.0, 0), enemy(&mut world, 0.0, 0.0, 2));
+        let bolt = bullet(&mut world, 1, false);
+        world.insert_resource(Contacts(vec![(bolt, dead), (bolt, alive)]));
+
+        assert_eq!(hit(&mut world), vec![]);
+        assert_eq!(world.get::<Health>(dead), Some(&Health(0)));
+        assert_eq!(world.get::<Health>(alive), Some(&Health(1)));
+    }
+
+    #[test]
+    fn targets_without_health_soak_up_projectiles() {
+        let mut world = World::new();
+        let hull = target(&mut world, SHIP, BOSS_LAYER, None);
+        let behind = enemy(&mut world, 0.0, 0.0, 2);
+        let bolt = bullet(&mut world, 1, false);
+        world.insert_resource(Contacts(vec![(bolt, hull), (bolt, behind)]));
+
+        assert_eq!(hit(&mut world), vec![]);
+        assert_eq!(world.get::<Health>(behind), Some(&Health(2)));
+    }
+
+    #[test]
+    fn missiles_turn_towards_the_closest_target_ahead() {
+        let mut world = World::new();
+        let missile = missile(&mut world, Vec2::new(100.0, 300.0));
+        enemy(&mut world, 290.0, 90.0, 1);
+        enemy(&mut world, 590.0, 490.0, 1);
+
+        // The turn is capped by the turn rate...
+        steer(&mut world, 0.1, SCREEN);
+        assert!((angle(&world, missile) + MISSILE_TURN_RATE * 0.1).abs() < 1e-9);
+
+        // ...but never overshoots the target, up and to the right.
+        steer(&mut world, 1.0, SCREEN);
+        assert!((angle(&world, missile) + PI / 4.0).abs() < 1e-9);
+
+        let Velocity(vel) = *world.get::<Velocity>(missile).unwrap();
+        assert!((vel.length() - MISSILE_SPEED).abs() < 1e-9);
+    }
+
+    #[test]
+    fn missiles_ignore_what_they_cannot_reach_or_hit() {
+        let mut world = World::new();
+        let missile = missile(&mut world, Vec2::new(100.0, 300.0));
+
+        // Behind the missile, off screen, and on a layer it cannot hit.
+        enemy(&mut world, 40.0, 100.0, 1);
+        enemy(&mut world, 900.0, 100.0, 1);
+        target(&mut world, Rectangle { x: 200.0, y: 100.0, w: 20.0, h: 20.0 }, ENEMY_BULLET_LAYER, None);
+
+        steer(&mut world, 0.1, SCREEN);
+        assert_eq!(angle(&world, missile), 0.0);
+    }
+
+    #[test]
+    fn beams_hit_once_even_through_freeze_frames() {
+        let mut world = World::new();
+        let beam = arsenal().get(WeaponKind::Laser).fire(&mut world, SHIP)[0];
+        let Body(rect) = *world.get::<Body>(beam).unwrap();
+        assert_eq!(rect.x + rect.w, SCREEN.w);
+
+        let victim = enemy(&mut world, 600.0, rect.y - 5.0, 10);
+        let mut collisions = Collisions::new(96.0);
+
+        // Freeze frames run the systems without any time passing.
+        for _ in 0..3 {
+            discharge_beams(&mut world);
+            collisions.detect(&mut world);
+            hit(&mut world);
+        }
+
+        assert_eq!(world.get::<Health>(victim), Some(&Health(7)));
+        assert!(world.is_alive(beam));
+    }
+
+    #[test]
+    fn beams_narrow_as_they_fade_out() {
+        let mut world = World::new();
+        let beam = arsenal().get(WeaponKind::Laser).fire(&mut world, SHIP)[0];
+        world.insert(beam, Lifetime(BEAM_LIFETIME / 2.0));
+
+        discharge_beams(&mut world);
+
+        let Body(rect) = *world.get::<Body>(beam).unwrap();
+        assert_eq!(rect.h, BEAM_H / 2.0);
+        assert_eq!(rect.center().y, SHIP.center().y);
+    }
+}